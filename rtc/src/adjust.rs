@@ -0,0 +1,97 @@
+use std::{collections::HashMap, io, path::PathBuf};
+use crate::colour_space::{Oklab, Oklch};
+use crate::colours::{
+    extract_current_colours,
+    is_key_selected,
+    parse_colour_keys_input,
+    parse_forced_keys_input,
+    update_kitty_config_with_colours,
+    ColourMap,
+    COLOUR_KEYS,
+};
+use crate::util::colour_swatch;
+
+/// How far `--warm`/`--cool` 1.0 moves a colour along OKLab's blue-yellow axis;
+/// a small share of it also goes to the green-red axis so warm reads as amber
+/// rather than pure yellow.
+const MAX_TEMPERATURE_SHIFT: f64 = 0.06;
+
+/// A global edit applied to every selected key. Signed fields move in either
+/// direction: a positive `lightness` lightens, a negative one darkens, and so on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColourAdjustment {
+    /// -1 to 1; the fraction of the way towards white (positive) or black (negative).
+    pub lightness: f64,
+    /// -1 to 1; relative change of OKLCH chroma.
+    pub chroma: f64,
+    /// Degrees added to the OKLCH hue.
+    pub hue_rotation: f64,
+    /// -1 to 1; warmer (positive) or cooler (negative).
+    pub temperature: f64,
+}
+
+impl ColourAdjustment {
+    pub fn is_noop(&self) -> bool {
+        self.lightness == 0.0 && self.chroma == 0.0 && self.hue_rotation == 0.0 && self.temperature == 0.0
+    }
+}
+
+/// Applies `adjustment` to one colour in OKLab/OKLCH, reducing chroma if the result
+/// would leave the sRGB gamut.
+pub fn adjust_hex(hex: &str, adjustment: &ColourAdjustment) -> Result<String, String> {
+    let mut lab = Oklab::from_hex(hex)?;
+    lab.b += adjustment.temperature * MAX_TEMPERATURE_SHIFT;
+    lab.a += adjustment.temperature * MAX_TEMPERATURE_SHIFT * 0.25;
+
+    let lch: Oklch = lab.into();
+    let l = if adjustment.lightness >= 0.0 {
+        lch.l + (1.0 - lch.l) * adjustment.lightness
+    } else {
+        lch.l * (1.0 + adjustment.lightness)
+    };
+    let c = lch.c * (1.0 + adjustment.chroma);
+    Ok(Oklch::new(l, c, lch.h + adjustment.hue_rotation).to_hex())
+}
+
+/// Adjusts the colours in kitty.conf selected by the `-e`/`-f` lists and writes
+/// them back.
+pub fn adjust_colours_in_kitty(
+    config_file_path: &PathBuf,
+    exception_keys_input: &Option<String>,
+    force_keys_input: &Option<String>,
+    adjustment: &ColourAdjustment,
+) -> Result<(), io::Error> {
+    if adjustment.is_noop() {
+        eprintln!("Warning: No adjustment given (use e.g. `--desaturate 0.2`). No changes applied.");
+        return Ok(());
+    }
+
+    let current_colours = extract_current_colours(config_file_path)?;
+    let forced_keys = parse_forced_keys_input(force_keys_input)?;
+    let excluded_keys = parse_colour_keys_input(exception_keys_input);
+
+    let mut adjusted: ColourMap = HashMap::new();
+    println!("\nAdjusted colours:");
+    for &key in COLOUR_KEYS.iter() {
+        if !is_key_selected(key, &forced_keys, &excluded_keys) {
+            continue;
+        }
+        let Some(old_hex) = current_colours.get(key) else {
+            continue;
+        };
+        let new_hex = adjust_hex(old_hex, adjustment)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to adjust '{}': {}", key, e)))?;
+        println!("  {:<11} {} #{}  → {} #{}", key, colour_swatch(old_hex), old_hex, colour_swatch(&new_hex), new_hex);
+        adjusted.insert(key.to_string(), new_hex);
+    }
+
+    if adjusted.is_empty() {
+        eprintln!("Warning: No colours selected for adjustment. No changes applied.");
+        return Ok(());
+    }
+    update_kitty_config_with_colours(config_file_path, &adjusted)?;
+
+    println!("\nKitty colours updated in config file!");
+    println!("Please restart Kitty manually to see the changes, as live reload is not reliably supported by your Kitty version.");
+    Ok(())
+}
@@ -0,0 +1,454 @@
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+use crate::colours::{ColourMap, COLOUR_KEYS, EXTENDED_COLOUR_KEYS};
+use crate::config::{get_colours_backup_path, list_colour_backup_names};
+use crate::util::is_valid_hex_code;
+use serde::{Deserialize, Serialize};
+use toml::Spanned;
+
+/// Version 1 has the metadata fields, a `[colours]` table with every `COLOUR_KEYS`
+/// entry and an optional `[extended_colours]` table.
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+pub const UNSET_COLOUR: &str = "unset";
+pub const SOURCE_TERMINAL: &str = "kitty";
+
+/// A colour backup as stored in `~/.config/rtc/<name>.rtc_colours`.
+///
+/// Keys missing from `colours` are unset: they were not present in the source config
+/// and are left untouched when the backup is loaded. `extended` holds whichever
+/// `EXTENDED_COLOUR_KEYS` the source had and is stored in an optional table.
+#[derive(Debug, Clone)]
+pub struct ColourBackup {
+    pub format_version: u32,
+    pub created: Option<String>,
+    pub terminal: Option<String>,
+    pub source_config: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub colours: ColourMap,
+    pub extended: ColourMap,
+}
+
+#[derive(Serialize)]
+struct BackupFile {
+    format_version: u32,
+    created: toml::value::Datetime,
+    terminal: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_config: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    colours: toml::Table,
+    #[serde(skip_serializing_if = "toml::Table::is_empty")]
+    extended_colours: toml::Table,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBackupFile {
+    format_version: Spanned<u32>,
+    created: toml::value::Datetime,
+    terminal: String,
+    #[serde(default)]
+    source_config: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    colours: Spanned<BTreeMap<Spanned<String>, Spanned<toml::Value>>>,
+    #[serde(default)]
+    extended_colours: BTreeMap<Spanned<String>, Spanned<toml::Value>>,
+}
+
+impl ColourBackup {
+    pub fn new(colours: ColourMap, source_config: Option<&Path>, description: Option<String>, tags: Vec<String>) -> Self {
+        ColourBackup {
+            format_version: BACKUP_FORMAT_VERSION,
+            created: Some(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+            terminal: Some(SOURCE_TERMINAL.to_string()),
+            source_config: source_config.map(|p| p.display().to_string()),
+            description,
+            tags,
+            colours,
+            extended: ColourMap::new(),
+        }
+    }
+
+    pub fn unset_keys(&self) -> Vec<&'static str> {
+        COLOUR_KEYS.iter()
+            .copied()
+            .filter(|key| !self.colours.contains_key(*key))
+            .collect()
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, io::Error> {
+        let created = self.created.clone()
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+        let created = created.parse::<toml::value::Datetime>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid backup creation time '{}': {}", created, e)))?;
+
+        let mut colours = toml::Table::new();
+        for &key in COLOUR_KEYS.iter() {
+            let value = match self.colours.get(key) {
+                Some(hex) => format!("#{}", hex),
+                None => UNSET_COLOUR.to_string(),
+            };
+            colours.insert(key.to_string(), toml::Value::String(value));
+        }
+        let mut extended_colours = toml::Table::new();
+        for &key in EXTENDED_COLOUR_KEYS.iter() {
+            if let Some(hex) = self.extended.get(key) {
+                extended_colours.insert(key.to_string(), toml::Value::String(format!("#{}", hex)));
+            }
+        }
+
+        let file = BackupFile {
+            format_version: BACKUP_FORMAT_VERSION,
+            created,
+            terminal: self.terminal.clone().unwrap_or_else(|| SOURCE_TERMINAL.to_string()),
+            source_config: self.source_config.clone(),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+            colours,
+            extended_colours,
+        };
+
+        toml::to_string(&file)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to serialise colour backup: {}", e)))
+    }
+}
+
+/// A single validation problem found in a backup file. `line` is 1-based.
+#[derive(Debug, Clone)]
+pub struct BackupIssue {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl BackupIssue {
+    fn at(line: usize, message: String) -> Self {
+        BackupIssue { line: Some(line), message }
+    }
+}
+
+impl fmt::Display for BackupIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+fn line_number_at(content: &str, byte_offset: usize) -> usize {
+    content[..byte_offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// Versioned backups always carry a top-level `format_version`; anything else is
+/// treated as the legacy `key#hex` format.
+pub fn is_versioned_backup(content: &str) -> bool {
+    content.lines().any(|line| line.trim_start().starts_with("format_version"))
+}
+
+/// Parses and strictly validates a backup, collecting every problem rather than
+/// stopping at the first one.
+pub fn check_backup(content: &str) -> Result<ColourBackup, Vec<BackupIssue>> {
+    if is_versioned_backup(content) {
+        check_versioned_backup(content)
+    } else {
+        check_legacy_backup(content)
+    }
+}
+
+fn check_versioned_backup(content: &str) -> Result<ColourBackup, Vec<BackupIssue>> {
+    let file: RawBackupFile = toml::from_str(content).map_err(|e| {
+        let line = e.span().map(|span| line_number_at(content, span.start));
+        vec![BackupIssue { line, message: e.message().trim().to_string() }]
+    })?;
+
+    let mut issues = Vec::new();
+    let version_line = line_number_at(content, file.format_version.span().start);
+    let format_version = *file.format_version.get_ref();
+    if format_version == 0 || format_version > BACKUP_FORMAT_VERSION {
+        issues.push(BackupIssue::at(
+            version_line,
+            format!("unsupported format version {} (this rtc supports version {}; upgrade rtc if the backup is newer)", format_version, BACKUP_FORMAT_VERSION),
+        ));
+    }
+
+    let mut colours = ColourMap::new();
+    let mut entries: Vec<_> = file.colours.get_ref().iter().collect();
+    entries.sort_by_key(|(key, _)| key.span().start);
+
+    for (key, value) in entries {
+        let line = line_number_at(content, key.span().start);
+        let key_name = key.get_ref().as_str();
+        if !COLOUR_KEYS.contains(&key_name) {
+            issues.push(BackupIssue::at(line, format!("unknown colour key '{}'", key_name)));
+            continue;
+        }
+        match value.get_ref().as_str() {
+            Some(UNSET_COLOUR) => {}
+            Some(hex) if hex.starts_with('#') && is_valid_hex_code(&hex[1..]) => {
+                colours.insert(key_name.to_string(), hex[1..].to_lowercase());
+            }
+            Some(hex) => issues.push(BackupIssue::at(
+                line,
+                format!("invalid value '{}' for '{}' (expected \"#rrggbb\" or \"{}\")", hex, key_name, UNSET_COLOUR),
+            )),
+            None => issues.push(BackupIssue::at(
+                line,
+                format!("value for '{}' must be a string (\"#rrggbb\" or \"{}\")", key_name, UNSET_COLOUR),
+            )),
+        }
+    }
+
+    let colours_line = line_number_at(content, file.colours.span().start);
+    for &key in COLOUR_KEYS.iter() {
+        if !file.colours.get_ref().keys().any(|k| k.get_ref() == key) {
+            issues.push(BackupIssue::at(
+                colours_line,
+                format!("missing colour key '{}' (use \"{}\" if it has no value)", key, UNSET_COLOUR),
+            ));
+        }
+    }
+
+    let mut extended = ColourMap::new();
+    for (key, value) in &file.extended_colours {
+        let line = line_number_at(content, key.span().start);
+        let key_name = key.get_ref().as_str();
+        if !EXTENDED_COLOUR_KEYS.contains(&key_name) {
+            issues.push(BackupIssue::at(line, format!("unknown extended colour key '{}'", key_name)));
+            continue;
+        }
+        match value.get_ref().as_str() {
+            Some(hex) if hex.starts_with('#') && is_valid_hex_code(&hex[1..]) => {
+                extended.insert(key_name.to_string(), hex[1..].to_lowercase());
+            }
+            _ => issues.push(BackupIssue::at(
+                line,
+                format!("invalid value for extended colour '{}' (expected \"#rrggbb\")", key_name),
+            )),
+        }
+    }
+
+    if !issues.is_empty() {
+        issues.sort_by_key(|issue| issue.line);
+        return Err(issues);
+    }
+
+    Ok(ColourBackup {
+        format_version,
+        created: Some(file.created.to_string()),
+        terminal: Some(file.terminal),
+        source_config: file.source_config,
+        description: file.description,
+        tags: file.tags,
+        colours,
+        extended,
+    })
+}
+
+/// Parses the original bare `key#hex` format. It has no metadata and no notion of
+/// unset keys, so keys that are absent are simply not restored.
+fn check_legacy_backup(content: &str) -> Result<ColourBackup, Vec<BackupIssue>> {
+    let mut colours = ColourMap::new();
+    let mut issues = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_number = idx + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let Some(hash_pos) = trimmed.find('#') else {
+            issues.push(BackupIssue::at(line_number, format!("expected 'key#rrggbb', found '{}'", trimmed)));
+            continue;
+        };
+        let key = trimmed[..hash_pos].trim();
+        let hex = trimmed[hash_pos + 1..].trim();
+
+        if !COLOUR_KEYS.contains(&key) {
+            issues.push(BackupIssue::at(line_number, format!("unknown colour key '{}'", key)));
+        } else if !is_valid_hex_code(hex) {
+            issues.push(BackupIssue::at(line_number, format!("invalid hex value '#{}' for '{}' (expected 6 hex digits)", hex, key)));
+        } else if colours.insert(key.to_string(), hex.to_lowercase()).is_some() {
+            issues.push(BackupIssue::at(line_number, format!("duplicate colour key '{}'", key)));
+        }
+    }
+
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+
+    Ok(ColourBackup {
+        format_version: 0,
+        created: None,
+        terminal: None,
+        source_config: None,
+        description: None,
+        tags: Vec::new(),
+        colours,
+        extended: ColourMap::new(),
+    })
+}
+
+pub fn read_colours_backup(backup_name: &Option<String>) -> Result<ColourBackup, io::Error> {
+    let backup_file_path = get_colours_backup_path(backup_name)?;
+
+    if !backup_file_path.exists() {
+        eprintln!("Error: Colour backup file not found at {}. Cannot load.", backup_file_path.display());
+        return Err(io::Error::new(io::ErrorKind::NotFound, "Colour backup file not found"));
+    }
+
+    let backup_content = fs::read_to_string(&backup_file_path)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to read colour backup: {}", e)))?;
+
+    check_backup(&backup_content).map_err(|issues| {
+        eprintln!("Error: Colour backup at {} is invalid:", backup_file_path.display());
+        for issue in &issues {
+            eprintln!("  {}", issue);
+        }
+        io::Error::new(io::ErrorKind::InvalidData, "Colour backup failed validation")
+    })
+}
+
+pub fn write_colours_backup(backup_name: &Option<String>, backup: &ColourBackup) -> Result<(), io::Error> {
+    let backup_file_path = get_colours_backup_path(backup_name)?;
+    let backup_content = backup.to_toml_string()?;
+
+    println!("Creating colour backup to: {}", backup_file_path.display());
+    fs::write(&backup_file_path, backup_content)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to write colour backup: {}", e)))
+}
+
+pub fn check_all_backups() -> Result<(), io::Error> {
+    let backup_names = list_colour_backup_names()?;
+    if backup_names.is_empty() {
+        println!("No colour backups found.");
+        return Ok(());
+    }
+
+    let mut invalid_count = 0;
+    for name in &backup_names {
+        let backup_file_path = get_colours_backup_path(&Some(name.clone()))?;
+        let backup_content = fs::read_to_string(&backup_file_path)
+            .map_err(|e| io::Error::new(e.kind(), format!("Failed to read colour backup '{}': {}", name, e)))?;
+
+        match check_backup(&backup_content) {
+            Ok(backup) => {
+                let format = if backup.format_version == 0 { "legacy" } else { "v1" };
+                println!("  ok       {} ({})", name, format);
+            }
+            Err(issues) => {
+                invalid_count += 1;
+                println!("  invalid  {}", name);
+                for issue in &issues {
+                    println!("             {}", issue);
+                }
+            }
+        }
+    }
+
+    println!("\nChecked {} backup(s), {} invalid.", backup_names.len(), invalid_count);
+    if invalid_count > 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} colour backup(s) failed validation", invalid_count)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_colours() -> ColourMap {
+        COLOUR_KEYS.iter().map(|key| (key.to_string(), "112233".to_string())).collect()
+    }
+
+    fn versioned_content() -> String {
+        ColourBackup::new(full_colours(), None, None, Vec::new()).to_toml_string().unwrap()
+    }
+
+    fn messages(issues: Vec<BackupIssue>) -> Vec<String> {
+        issues.into_iter().map(|issue| issue.to_string()).collect()
+    }
+
+    #[test]
+    fn versioned_round_trip() {
+        let content = versioned_content();
+        assert!(is_versioned_backup(&content));
+        let backup = check_backup(&content).unwrap();
+        assert_eq!(backup.format_version, BACKUP_FORMAT_VERSION);
+        assert_eq!(backup.colours, full_colours());
+    }
+
+    #[test]
+    fn legacy_format_is_detected() {
+        let content = "foreground#ffffff\nbackground#000000\n";
+        assert!(!is_versioned_backup(content));
+        let backup = check_backup(content).unwrap();
+        assert_eq!(backup.format_version, 0);
+        assert_eq!(backup.colours.len(), 2);
+    }
+
+    #[test]
+    fn unknown_key_is_reported() {
+        let content = versioned_content().replace("color15 =", "color16 =");
+        let issues = messages(check_backup(&content).unwrap_err());
+        assert!(issues.iter().any(|m| m.contains("unknown colour key 'color16'")));
+        assert!(issues.iter().any(|m| m.contains("missing colour key 'color15'")));
+
+        let issues = messages(check_backup("foregrund#ffffff\n").unwrap_err());
+        assert_eq!(issues, vec!["line 1: unknown colour key 'foregrund'"]);
+    }
+
+    #[test]
+    fn unknown_top_level_field_is_reported() {
+        let content = versioned_content().replace("format_version", "format_versoin = 1\nformat_version");
+        let issues = messages(check_backup(&content).unwrap_err());
+        assert!(issues[0].starts_with("line 1: unknown field `format_versoin`"));
+    }
+
+    #[test]
+    fn bad_hex_is_reported() {
+        let content = versioned_content().replacen("\"#112233\"", "\"#11223\"", 1);
+        let issues = messages(check_backup(&content).unwrap_err());
+        assert!(issues.iter().any(|m| m.contains("invalid value '#11223'")));
+
+        let issues = messages(check_backup("foreground#ffffff\nbackground#zzzzzz\n").unwrap_err());
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].starts_with("line 2: invalid hex value '#zzzzzz'"));
+    }
+
+    #[test]
+    fn duplicate_keys_are_reported() {
+        let content = versioned_content().replace("color15 =", "color14 =");
+        assert!(check_backup(&content).is_err());
+
+        let issues = messages(check_backup("foreground#ffffff\nforeground#000000\n").unwrap_err());
+        assert_eq!(issues, vec!["line 2: duplicate colour key 'foreground'"]);
+    }
+
+    #[test]
+    fn missing_key_is_reported() {
+        let content: String = versioned_content()
+            .lines()
+            .filter(|line| !line.starts_with("cursor ="))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let issues = messages(check_backup(&content).unwrap_err());
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("missing colour key 'cursor'"));
+    }
+
+    #[test]
+    fn unset_keys_are_accepted() {
+        let mut colours = full_colours();
+        colours.remove("color14");
+        let content = ColourBackup::new(colours, None, None, Vec::new()).to_toml_string().unwrap();
+        assert!(content.contains("color14 = \"unset\""));
+        assert_eq!(check_backup(&content).unwrap().unset_keys(), vec!["color14"]);
+    }
+}
@@ -0,0 +1,294 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+use crate::backup::{read_colours_backup, write_colours_backup, ColourBackup};
+use crate::colour_space::{mix_hex, Oklab};
+use crate::colours::{extract_current_colours, load_colours_from_backup, resolve_colour_key, ColourMap, COLOUR_KEYS};
+use crate::config::require_kitty_config_path;
+use crate::util::is_valid_hex_code;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base16System {
+    /// 16 slots, base00 to base0F
+    Base16,
+    /// base16 plus darker backgrounds (base10, base11) and bright ANSI colours (base12 to base17)
+    Base24,
+}
+
+impl Base16System {
+    fn name(self) -> &'static str {
+        match self {
+            Base16System::Base16 => "base16",
+            Base16System::Base24 => "base24",
+        }
+    }
+
+    fn slots(self) -> Vec<String> {
+        let count = match self {
+            Base16System::Base16 => 16,
+            Base16System::Base24 => 24,
+        };
+        (0..count).map(|i| format!("base{:02X}", i)).collect()
+    }
+
+    /// The standard slot for each kitty key, following the tinted-theming terminal
+    /// templates. base16 reuses the normal ANSI slots for the brights.
+    fn default_mapping(self) -> Vec<(&'static str, &'static str)> {
+        let brights = match self {
+            Base16System::Base16 => ["base03", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base07"],
+            Base16System::Base24 => ["base02", "base12", "base14", "base13", "base16", "base17", "base15", "base07"],
+        };
+        let white = match self {
+            Base16System::Base16 => "base05",
+            Base16System::Base24 => "base06",
+        };
+        let mut mapping = vec![
+            ("foreground", "base05"),
+            ("background", "base00"),
+            ("cursor", "base05"),
+            ("color0", "base00"),
+            ("color1", "base08"),
+            ("color2", "base0B"),
+            ("color3", "base0A"),
+            ("color4", "base0D"),
+            ("color5", "base0E"),
+            ("color6", "base0C"),
+            ("color7", white),
+        ];
+        for (i, slot) in brights.into_iter().enumerate() {
+            mapping.push((COLOUR_KEYS[11 + i], slot));
+        }
+        mapping
+    }
+}
+
+/// Resolves the kitty key to slot mapping: the standard one for `system`, with
+/// `overrides` such as `cursor=base0D,c0=base01` applied on top.
+pub fn base16_mapping(system: Base16System, overrides: &Option<String>) -> Result<Vec<(String, String)>, io::Error> {
+    let mut mapping: Vec<(String, String)> = system.default_mapping()
+        .into_iter()
+        .map(|(key, slot)| (key.to_string(), slot.to_string()))
+        .collect();
+    let slots = system.slots();
+
+    for part in overrides.as_deref().unwrap_or("").split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let invalid = |message: String| {
+            eprintln!("Error: Invalid mapping '{}': {}.", part, message);
+            io::Error::new(io::ErrorKind::InvalidInput, "Invalid base16 mapping")
+        };
+        let Some((key_part, slot_part)) = part.split_once('=') else {
+            return Err(invalid("expected KEY=SLOT (e.g., 'cursor=base0D')".to_string()));
+        };
+        let Some(key) = resolve_colour_key(key_part.trim()) else {
+            return Err(invalid(format!("unknown colour key '{}'", key_part.trim())));
+        };
+        let Some(slot) = slots.iter().find(|s| s.eq_ignore_ascii_case(slot_part.trim())) else {
+            return Err(invalid(format!("'{}' is not a {} slot", slot_part.trim(), system.name())));
+        };
+        if let Some(entry) = mapping.iter_mut().find(|(k, _)| k == key) {
+            entry.1 = slot.clone();
+        }
+    }
+    Ok(mapping)
+}
+
+/// Both the current tinted-theming layout (metadata plus a `palette` table) and the
+/// original flat layout (`scheme`, `author` and the slots at the top level).
+#[derive(Deserialize)]
+struct SchemeFile {
+    #[serde(default)]
+    system: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    scheme: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    palette: Option<BTreeMap<String, String>>,
+    #[serde(flatten)]
+    rest: BTreeMap<String, serde_norway::Value>,
+}
+
+#[derive(Serialize)]
+struct SchemeFileOut {
+    system: String,
+    name: String,
+    author: String,
+    variant: String,
+    palette: BTreeMap<String, String>,
+}
+
+/// Imports a base16 or base24 scheme YAML file as a backup, optionally applying it.
+/// The system is taken from the file's `system` field, or from which slots it has.
+pub fn import_base16(
+    file_path: &Path,
+    backup_name: Option<String>,
+    mapping_overrides: &Option<String>,
+    apply: bool,
+) -> Result<(), io::Error> {
+    let content = fs::read_to_string(file_path).map_err(|e| {
+        eprintln!("Error: Failed to read scheme file {}: {}", file_path.display(), e);
+        io::Error::new(e.kind(), format!("Failed to read scheme file: {}", e))
+    })?;
+    let file: SchemeFile = serde_norway::from_str(&content).map_err(|e| {
+        eprintln!("Error: {} is not valid scheme YAML: {}", file_path.display(), e);
+        io::Error::new(io::ErrorKind::InvalidData, "Invalid scheme YAML")
+    })?;
+
+    let raw_palette: BTreeMap<String, String> = match file.palette {
+        Some(palette) => palette,
+        None => file.rest.iter()
+            .filter(|(key, _)| key.starts_with("base"))
+            .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+            .collect(),
+    };
+    let mut palette: BTreeMap<String, String> = BTreeMap::new();
+    for (slot, value) in raw_palette {
+        let hex = value.trim().trim_start_matches('#').to_lowercase();
+        if !is_valid_hex_code(&hex) {
+            eprintln!("Error: Invalid colour '{}' for {} in {}.", value, slot, file_path.display());
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid scheme colour"));
+        }
+        palette.insert(slot.to_lowercase(), hex);
+    }
+
+    let system = match file.system.as_deref() {
+        Some("base24") => Base16System::Base24,
+        Some(_) => Base16System::Base16,
+        None if palette.contains_key("base12") => Base16System::Base24,
+        None => Base16System::Base16,
+    };
+    let missing: Vec<String> = system.slots().into_iter().filter(|slot| !palette.contains_key(&slot.to_lowercase())).collect();
+    if !missing.is_empty() {
+        eprintln!("Error: {} is missing {} slot(s): {}.", file_path.display(), system.name(), missing.join(", "));
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Incomplete scheme"));
+    }
+
+    let mut colours = ColourMap::new();
+    for (key, slot) in base16_mapping(system, mapping_overrides)? {
+        colours.insert(key, palette[&slot.to_lowercase()].clone());
+    }
+
+    let title = file.name.or(file.scheme).unwrap_or_else(|| "Untitled scheme".to_string());
+    let description = match file.author {
+        Some(author) => format!("{} by {}", title, author),
+        None => title,
+    };
+    let name = backup_name.unwrap_or_else(|| {
+        file_path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| "imported".to_string())
+    });
+
+    let backup = ColourBackup::new(colours, Some(file_path), Some(description), vec!["imported".to_string(), system.name().to_string()]);
+    write_colours_backup(&Some(name.clone()), &backup)?;
+    println!("{} scheme imported as '{}'.", system.name(), name);
+
+    if apply {
+        load_colours_from_backup(&require_kitty_config_path()?, Some(name), &None, &None)
+    } else {
+        println!("Load it with `rtc -l -n {}`.", name);
+        Ok(())
+    }
+}
+
+/// Builds the base16/base24 palette for `colours` by reversing the mapping. The
+/// first key (in `COLOUR_KEYS` order) mapped to a slot provides its value; slots no
+/// key maps to are blended in OKLab from their neighbours, e.g. the base01/base02
+/// background shades from the background and foreground.
+pub fn colours_to_base16(colours: &ColourMap, system: Base16System, mapping: &[(String, String)]) -> Result<BTreeMap<String, String>, String> {
+    let mut palette: BTreeMap<String, String> = BTreeMap::new();
+    for &key in COLOUR_KEYS.iter() {
+        let Some((_, slot)) = mapping.iter().find(|(k, _)| k == key) else {
+            continue;
+        };
+        if let Some(hex) = colours.get(key) {
+            palette.entry(slot.clone()).or_insert_with(|| hex.clone());
+        }
+    }
+
+    let get = |palette: &BTreeMap<String, String>, slot: &str| -> Result<String, String> {
+        palette.get(slot).cloned().ok_or_else(|| format!("{} has no colour to derive from", slot))
+    };
+    // Each derived slot: (slot, from, to, ratio), filled in order so later
+    // entries can build on earlier ones. A ratio of 0 copies `from`.
+    let mut derived: Vec<(&str, &str, &str, f64)> = vec![
+        ("base07", "base05", "base05", 0.0),
+        ("base03", "base00", "base05", 0.45),
+        ("base01", "base00", "base05", 0.08),
+        ("base02", "base00", "base05", 0.18),
+        ("base04", "base03", "base05", 0.5),
+        ("base06", "base05", "base07", 0.5),
+        ("base09", "base08", "base0A", 0.5),
+        ("base0F", "base08", "base00", 0.35),
+    ];
+    if system == Base16System::Base24 {
+        derived.extend([
+            ("base10", "base00", "000000", 0.25),
+            ("base11", "base00", "000000", 0.5),
+            ("base12", "base08", "base08", 0.0),
+            ("base13", "base0A", "base0A", 0.0),
+            ("base14", "base0B", "base0B", 0.0),
+            ("base15", "base0C", "base0C", 0.0),
+            ("base16", "base0D", "base0D", 0.0),
+            ("base17", "base0E", "base0E", 0.0),
+        ]);
+    }
+    for (slot, from, to, ratio) in derived {
+        if palette.contains_key(slot) {
+            continue;
+        }
+        // "000000" is black rather than a slot, for base24's darker backgrounds.
+        let to_hex = if to == "000000" { to.to_string() } else { get(&palette, to)? };
+        let hex = mix_hex(&get(&palette, from)?, &to_hex, ratio)?;
+        palette.insert(slot.to_string(), hex);
+    }
+
+    let missing: Vec<String> = system.slots().into_iter().filter(|slot| !palette.contains_key(slot)).collect();
+    if !missing.is_empty() {
+        return Err(format!("no key maps to {} and it cannot be derived", missing.join(", ")));
+    }
+    Ok(palette)
+}
+
+/// Exports a backup, or the live kitty.conf when `backup_name` is `None`, as a
+/// base16/base24 scheme. Writes to `output` or prints to stdout.
+pub fn export_base16(
+    backup_name: Option<String>,
+    output: Option<&Path>,
+    system: Base16System,
+    mapping_overrides: &Option<String>,
+) -> Result<(), io::Error> {
+    let (colours, scheme_name) = match &backup_name {
+        Some(name) => (read_colours_backup(&backup_name)?.colours, name.clone()),
+        None => (extract_current_colours(&require_kitty_config_path()?)?, "kitty".to_string()),
+    };
+    let mapping = base16_mapping(system, mapping_overrides)?;
+    let palette = colours_to_base16(&colours, system, &mapping).map_err(|e| {
+        eprintln!("Error: Cannot export as {}: {}.", system.name(), e);
+        io::Error::new(io::ErrorKind::InvalidData, "Cannot export scheme")
+    })?;
+
+    let variant = match palette.get("base00").map(|hex| Oklab::from_hex(hex)) {
+        Some(Ok(lab)) if lab.l >= 0.5 => "light",
+        _ => "dark",
+    };
+    let file = SchemeFileOut {
+        system: system.name().to_string(),
+        name: scheme_name,
+        author: "rtc".to_string(),
+        variant: variant.to_string(),
+        palette: palette.into_iter().map(|(slot, hex)| (slot, format!("#{}", hex))).collect(),
+    };
+    let yaml = serde_norway::to_string(&file)
+        .map_err(|e| io::Error::other(format!("Failed to serialise scheme: {}", e)))?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, yaml)
+                .map_err(|e| io::Error::new(e.kind(), format!("Failed to write {}: {}", path.display(), e)))?;
+            println!("Exported {} scheme to {}.", system.name(), path.display());
+        }
+        None => print!("{}", yaml),
+    }
+    Ok(())
+}
@@ -1,15 +1,27 @@
-use clap::Parser;
+use std::path::PathBuf;
+use std::time::Duration;
+use clap::{Parser, Subcommand};
+use crate::contrast::{ContrastFix, ContrastMetric};
+use crate::cvd::CvdType;
+use crate::lint::OutputFormat;
+use crate::generate::{PaletteStrategy, SchemeVariant, DEFAULT_BRIGHT_AMOUNT};
+use crate::transition::TransitionMethod;
+use crate::base16::Base16System;
 
 #[derive(Parser, Debug)]
 #[command(
     name = "rtc",
     author = "Rod",
     version,
+    disable_help_flag = true,
+    args_conflicts_with_subcommands = true,
     about = "Rod's Terminal Colours for Kitty",
     long_about = "Rod's Terminal Colours (rtc) is a CLI tool to manage different colour functionalities. It allows you to generate random colour schemes, create backups of your current one, load previously saved ones, print current colours, and shuffle existing colours. Colours are applied to ~/.config/kitty/kitty.kitty.conf or ~/.kitty.kitty.conf.",
 )]
-
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Generate and apply a random Kitty colour scheme
     #[arg(short = 'r', long = "random", conflicts_with_all = &["backup", "load", "get_colours", "shuffle", "set_colour"])]
     pub random_colours: bool,
@@ -18,8 +30,9 @@ pub struct Args {
     #[arg(short = 'b', long = "backup", conflicts_with_all = &["random_colours", "load", "get_colours", "shuffle", "exception_keys", "force_keys", "set_colour", "hex_values"])]
     pub backup: bool,
 
-    /// Load a saved Kitty colour configuration backup
-    #[arg(short = 'l', long = "load", conflicts_with_all = &["random_colours", "backup", "get_colours", "shuffle", "exception_keys", "force_keys", "set_colour", "hex_values"])]
+    /// Load a saved Kitty colour configuration backup.
+    /// Combine with -f or -e to restore only some of its colours.
+    #[arg(short = 'l', long = "load", conflicts_with_all = &["random_colours", "backup", "get_colours", "shuffle", "set_colour", "hex_values"])]
     pub load: bool,
 
     /// Print the currently applied 19 prominent colours from Kitty's config
@@ -35,13 +48,13 @@ pub struct Args {
     #[arg(short = 'n', long = "name", value_name = "NAME")]
     pub name: Option<String>,
 
-    /// Specify colour keys to exclude from randomization/shuffling (e.g., 'bg' or '(fg, c0, c7)').
-    /// Use with -r or -s. Conflicts with --force.
+    /// Specify colour keys to exclude from randomization/shuffling/loading (e.g., 'bg', '(fg, c0, c7)' or 'c0-c7').
+    /// Use with -r, -s or -l. Conflicts with --force.
     #[arg(short = 'e', long = "exception", value_name = "KEYS", conflicts_with = "force_keys")]
     pub exception_keys: Option<String>,
 
-    /// Specify colour keys to ONLY apply randomization/shuffling (with -r or -s) OR to set specific colours (with -c).
-    /// (e.g., 'bg' or '(fg, c0, c7)'). Conflicts with --exception.
+    /// Specify colour keys to ONLY apply randomization/shuffling/loading (with -r, -s or -l) OR to set specific colours (with -c).
+    /// (e.g., 'bg', '(fg, c0, c7)' or, except with -c, 'c8-c15'). Conflicts with --exception.
     #[arg(short = 'f', long = "force", value_name = "KEYS")]
     pub force_keys: Option<String>,
 
@@ -50,8 +63,484 @@ pub struct Args {
     #[arg(short = 'c', long = "set-colour", conflicts_with_all = &["random_colours", "backup", "load", "get_colours", "shuffle"], requires_all = &["force_keys", "hex_values"])]
     pub set_colour: bool,
 
-    /// Comma-separated list of 6-digit hex colour values (e.g., '123456,ABCDEF') corresponding to --force (-f) keys.
-    /// Use with -c/--set-colour.
+    /// Comma-separated list of 6-digit hex colour values or CSS/X11 colour names (e.g., '123456,ABCDEF' or 'navy,ivory') corresponding to --force (-f) keys.
+    /// Use with -c/--set-colour. With -r/--random, the colours (optionally weighted, e.g. '123456:3') form the pool to draw from.
     #[arg(short = 'h', long = "hex-values", value_name = "HEX_CODES")]
     pub hex_values: Option<String>,
-}
\ No newline at end of file
+
+    /// Seed for the random number generator used by -r and -s, to reproduce a previous result (stable across machines for the same rtc version)
+    #[arg(long = "seed", value_name = "U64")]
+    pub seed: Option<u64>,
+
+    /// Minimum contrast of the foreground and ANSI colours against the background, for -r, -s and -c
+    /// (a WCAG ratio such as 4.5, or an APCA Lc value such as 60 with --contrast-metric apca)
+    #[arg(long = "min-contrast", value_name = "VALUE", value_parser = parse_min_contrast)]
+    pub min_contrast: Option<f64>,
+
+    /// Contrast metric for --min-contrast (default: wcag)
+    #[arg(long = "contrast-metric", value_enum, value_name = "METRIC", requires = "min_contrast")]
+    pub contrast_metric: Option<ContrastMetric>,
+
+    /// What to do with colours below --min-contrast (default: adjust)
+    #[arg(long = "contrast-fix", value_enum, value_name = "FIX", requires = "min_contrast")]
+    pub contrast_fix: Option<ContrastFix>,
+
+    /// With -s/--shuffle, only swap within fg/bg/cursor, within color0-7 and within color8-15
+    #[arg(long = "within-groups", requires = "shuffle")]
+    pub within_groups: bool,
+
+    /// With -s/--shuffle, keep the background darker (or lighter) than the foreground as it is now
+    #[arg(long = "keep-bg-fg-order", requires = "shuffle")]
+    pub keep_bg_fg_order: bool,
+
+    /// With -s/--shuffle, guarantee that every shuffled key ends up with a different colour
+    #[arg(long = "derangement", requires = "shuffle")]
+    pub derangement: bool,
+
+    /// With -g/--get-colours, render swatches as seen with a colour vision deficiency
+    #[arg(long = "simulate", value_enum, value_name = "TYPE", requires = "get_colours")]
+    pub simulate: Option<CvdType>,
+
+    /// Palette generation strategy for -r/--random (default: random)
+    #[arg(long = "strategy", value_enum, value_name = "STRATEGY", requires = "random_colours")]
+    pub strategy: Option<PaletteStrategy>,
+
+    /// Base hue in degrees (0-360) for --strategy; picked at random if omitted. With 'semantic' it only tints the greys.
+    #[arg(long = "hue", value_name = "DEGREES", requires = "strategy", value_parser = parse_hue)]
+    pub hue: Option<f64>,
+
+    /// Maximum hue deviation in degrees around each ANSI colour's target hue (default: 12). Only for --strategy semantic.
+    #[arg(long = "hue-jitter", value_name = "DEGREES", requires = "strategy", value_parser = parse_hue)]
+    pub hue_jitter: Option<f64>,
+
+    /// OKLCH chroma range for color1-6, as MIN-MAX (default: 0.10-0.16). Only for --strategy semantic.
+    #[arg(long = "chroma", value_name = "MIN-MAX", requires = "strategy", value_parser = parse_unit_range)]
+    pub chroma: Option<(f64, f64)>,
+
+    /// OKLCH lightness range for color1-6, as MIN-MAX (default: 0.62-0.72). Only for --strategy semantic.
+    #[arg(long = "lightness", value_name = "MIN-MAX", requires = "strategy", value_parser = parse_unit_range)]
+    pub lightness: Option<(f64, f64)>,
+
+    /// With -r/--random, draw colours from the colours of this backup
+    #[arg(long = "pool-backup", value_name = "NAME", requires = "random_colours", conflicts_with_all = &["pool_file", "hex_values", "strategy"])]
+    pub pool_backup: Option<String>,
+
+    /// With -r/--random, draw colours from a file of hex codes, one 'HEX' or 'HEX:WEIGHT' per line
+    #[arg(long = "pool-file", value_name = "PATH", requires = "random_colours", conflicts_with_all = &["hex_values", "strategy"])]
+    pub pool_file: Option<PathBuf>,
+
+    /// With a colour pool, use each pool colour at most once
+    #[arg(long = "without-replacement")]
+    pub without_replacement: bool,
+
+    /// After -r/--random or -c/--set-colour, recompute color8-15 from color0-7
+    #[arg(long = "derive-brights")]
+    pub derive_brights: bool,
+
+    /// How far --derive-brights moves lightness towards white, 0 to 1 (default: 0.25)
+    #[arg(long = "bright-amount", value_name = "AMOUNT", requires = "derive_brights", value_parser = parse_ratio)]
+    pub bright_amount: Option<f64>,
+
+    /// Free-form description stored in the backup's metadata. Use with -b/--backup.
+    #[arg(long = "description", value_name = "TEXT", requires = "backup")]
+    pub description: Option<String>,
+
+    /// Comma-separated tags stored in the backup's metadata (e.g., 'dark,warm'). Use with -b/--backup.
+    #[arg(long = "tags", value_name = "TAGS", requires = "backup")]
+    pub tags: Option<String>,
+
+    /// Print help (-h is taken by --hex-values)
+    #[arg(long = "help", action = clap::ArgAction::Help)]
+    pub help: Option<bool>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Manage stored colour backups
+    Backups {
+        #[command(subcommand)]
+        action: BackupsCommand,
+    },
+
+    /// Import a kitty theme .conf file (e.g., from kitty-themes) as a backup
+    Import {
+        /// Path to the theme file
+        file: PathBuf,
+
+        /// Backup name to store it under (default: the file name without extension)
+        #[arg(short = 'n', long = "name", value_name = "NAME")]
+        name: Option<String>,
+
+        /// Also apply the imported theme to kitty.conf
+        #[arg(long = "apply")]
+        apply: bool,
+    },
+
+    /// Import or export base16/base24 scheme YAML
+    Base16 {
+        #[command(subcommand)]
+        action: Base16Command,
+    },
+
+    /// Import or export iTerm2 .itermcolors presets
+    Iterm {
+        #[command(subcommand)]
+        action: ItermCommand,
+    },
+
+    /// List or apply the well-known schemes bundled with rtc
+    Themes {
+        #[command(subcommand)]
+        action: ThemesCommand,
+    },
+
+    /// Switch backups by time of day using ~/.config/rtc/schedule
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleCommand,
+    },
+
+    /// Build a new theme from two saved backups and store it as a new backup
+    Mix {
+        /// Name of the first backup (A)
+        a: String,
+
+        /// Name of the second backup (B)
+        b: String,
+
+        /// Name of the backup to create
+        #[arg(short = 'n', long = "name", value_name = "NAME")]
+        name: String,
+
+        /// Colour keys copied verbatim from A (e.g., 'c0-c7' or 'bg,fg')
+        #[arg(long = "from-a", value_name = "KEYS")]
+        from_a: Option<String>,
+
+        /// Colour keys copied verbatim from B (e.g., 'c8-c15')
+        #[arg(long = "from-b", value_name = "KEYS")]
+        from_b: Option<String>,
+
+        /// Interpolation ratio in OKLab for all other keys: 0 is pure A, 1 is pure B
+        #[arg(long = "ratio", value_name = "RATIO", default_value_t = 0.5, value_parser = parse_ratio)]
+        ratio: f64,
+
+        /// Per-key ratio overrides (e.g., 'bg=0.2,c8-c15=0.75')
+        #[arg(long = "key-ratio", value_name = "KEY=RATIO")]
+        key_ratio: Option<String>,
+    },
+
+    /// Recompute the bright colours (color8-15) from the normal colours (color0-7)
+    DeriveBrights {
+        /// How far lightness moves towards white, 0 to 1
+        #[arg(long = "bright-amount", alias = "amount", value_name = "AMOUNT", default_value_t = DEFAULT_BRIGHT_AMOUNT, value_parser = parse_ratio)]
+        amount: f64,
+
+        /// Bright (or normal) colour keys to leave untouched (e.g., 'c8' or 'c12-c15')
+        #[arg(short = 'e', long = "exception", value_name = "KEYS", conflicts_with = "force_keys")]
+        exception_keys: Option<String>,
+
+        /// Only derive these brights, or the brights of these normals (e.g., 'c9' or 'c1-c3')
+        #[arg(short = 'f', long = "force", value_name = "KEYS")]
+        force_keys: Option<String>,
+    },
+
+    /// Generate a complete scheme around a single accent colour
+    Seed {
+        /// Accent colour as a hex code (e.g., '#3b82f6')
+        colour: String,
+
+        /// Generate a dark or a light scheme
+        #[arg(long = "variant", value_enum, default_value = "dark")]
+        variant: SchemeVariant,
+
+        /// Save the scheme as a named backup instead of applying it
+        #[arg(short = 'n', long = "name", value_name = "NAME")]
+        name: Option<String>,
+    },
+
+    /// Audit the live config or a backup for readability problems
+    Lint {
+        /// Name of the backup to lint (defaults to the live kitty.conf colours)
+        name: Option<String>,
+
+        /// Output format; json is meant for CI
+        #[arg(long = "format", value_enum, default_value = "text")]
+        format: OutputFormat,
+
+        /// Minimum WCAG contrast ratio between foreground and background
+        #[arg(long = "min-contrast", value_name = "RATIO", default_value_t = 4.5)]
+        min_contrast: f64,
+
+        /// Minimum CIEDE2000 ΔE between two normal (or two bright) ANSI colours
+        #[arg(long = "min-delta-e", value_name = "DELTA_E", default_value_t = 8.0)]
+        min_delta_e: f64,
+
+        /// Minimum ΔE between those pairs under simulated colour blindness; 0 skips the check.
+        /// The default is about the smallest difference most people can notice.
+        #[arg(long = "min-cvd-delta-e", value_name = "DELTA_E", default_value_t = 2.0)]
+        min_cvd_delta_e: f64,
+
+        /// Exit with a non-zero status on warnings as well as errors
+        #[arg(long = "deny-warnings")]
+        deny_warnings: bool,
+    },
+
+    /// Lighten, darken, (de)saturate, hue-rotate or warm/cool the current colours
+    Adjust {
+        /// Move lightness this fraction of the way towards white, 0 to 1
+        #[arg(long = "lighten", value_name = "AMOUNT", value_parser = parse_ratio, conflicts_with = "darken")]
+        lighten: Option<f64>,
+
+        /// Move lightness this fraction of the way towards black, 0 to 1
+        #[arg(long = "darken", value_name = "AMOUNT", value_parser = parse_ratio)]
+        darken: Option<f64>,
+
+        /// Increase chroma by this fraction, 0 to 1
+        #[arg(long = "saturate", value_name = "AMOUNT", value_parser = parse_ratio, conflicts_with = "desaturate")]
+        saturate: Option<f64>,
+
+        /// Decrease chroma by this fraction, 0 to 1 (1 gives greys)
+        #[arg(long = "desaturate", value_name = "AMOUNT", value_parser = parse_ratio)]
+        desaturate: Option<f64>,
+
+        /// Rotate every hue by this many degrees, -360 to 360
+        #[arg(long = "hue-rotate", value_name = "DEGREES", value_parser = parse_hue_rotation, allow_negative_numbers = true)]
+        hue_rotate: Option<f64>,
+
+        /// Shift colours towards amber, 0 to 1
+        #[arg(long = "warm", value_name = "AMOUNT", value_parser = parse_ratio, conflicts_with = "cool")]
+        warm: Option<f64>,
+
+        /// Shift colours towards blue, 0 to 1
+        #[arg(long = "cool", value_name = "AMOUNT", value_parser = parse_ratio)]
+        cool: Option<f64>,
+
+        /// Colour keys to leave untouched (e.g., 'bg' or 'c0-c7')
+        #[arg(short = 'e', long = "exception", value_name = "KEYS", conflicts_with = "force_keys")]
+        exception_keys: Option<String>,
+
+        /// Only adjust these colour keys (e.g., 'c1-c6,c9-c14')
+        #[arg(short = 'f', long = "force", value_name = "KEYS")]
+        force_keys: Option<String>,
+    },
+
+    /// Turn a dark scheme into a light one (or vice versa) by flipping OKLCH lightness
+    Invert {
+        /// Backup to invert; the live kitty.conf is used when omitted
+        source: Option<String>,
+
+        /// Save the inverted scheme as a named backup instead of applying it
+        #[arg(short = 'n', long = "name", value_name = "NAME")]
+        name: Option<String>,
+    },
+
+    /// Gradually fade the running terminal from one backup to another
+    Transition {
+        /// Backup to start from
+        from: String,
+
+        /// Backup to end on; it is also written to kitty.conf
+        to: String,
+
+        /// Total length of the fade (e.g., '2s', '500ms', '1m')
+        #[arg(long = "duration", value_name = "DURATION", default_value = "2s", value_parser = parse_duration)]
+        duration: Duration,
+
+        /// Number of intermediate palettes
+        #[arg(long = "steps", value_name = "N", default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..=1000))]
+        steps: u32,
+
+        /// How the intermediate palettes reach the terminal
+        #[arg(long = "method", value_enum, default_value = "osc")]
+        method: TransitionMethod,
+    },
+
+    /// Extract a colour scheme from a PNG or JPEG image
+    FromImage {
+        /// Path to the image
+        image: PathBuf,
+
+        /// Generate a dark or a light scheme
+        #[arg(long = "variant", value_enum, default_value = "dark")]
+        variant: SchemeVariant,
+
+        /// Number of colour clusters to extract from the image
+        #[arg(long = "clusters", value_name = "K", default_value_t = 16, value_parser = clap::value_parser!(u16).range(2..=64))]
+        clusters: u16,
+
+        /// Save the scheme as a named backup instead of applying it
+        #[arg(short = 'n', long = "name", value_name = "NAME")]
+        name: Option<String>,
+    },
+
+    /// Compare two backups, or a backup against the live kitty.conf, sorted by ΔE
+    Diff {
+        /// Name of the backup to compare from
+        a: String,
+
+        /// Name of the backup to compare to (defaults to the live kitty.conf colours)
+        b: Option<String>,
+    },
+}
+
+pub fn parse_hue(s: &str) -> Result<f64, String> {
+    let hue: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if (0.0..=360.0).contains(&hue) {
+        Ok(hue)
+    } else {
+        Err(format!("hue must be between 0 and 360 degrees, got {}", hue))
+    }
+}
+
+/// Parses durations such as '2s', '1.5s', '500ms' or '1m'. A bare number is seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, scale) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 0.001)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1.0)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60.0)
+    } else {
+        (s, 1.0)
+    };
+    let value: f64 = number.trim().parse().map_err(|_| format!("'{}' is not a duration (e.g., '2s' or '500ms')", s))?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(format!("duration must be a positive number, got '{}'", s));
+    }
+    Ok(Duration::from_secs_f64(value * scale))
+}
+
+pub fn parse_hue_rotation(s: &str) -> Result<f64, String> {
+    let degrees: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if (-360.0..=360.0).contains(&degrees) {
+        Ok(degrees)
+    } else {
+        Err(format!("hue rotation must be between -360 and 360 degrees, got {}", degrees))
+    }
+}
+
+pub fn parse_ratio(s: &str) -> Result<f64, String> {
+    let ratio: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if (0.0..=1.0).contains(&ratio) {
+        Ok(ratio)
+    } else {
+        Err(format!("ratio must be between 0 and 1, got {}", ratio))
+    }
+}
+
+/// Parses a --min-contrast value. The range depends on --contrast-metric, so this
+/// only accepts what either metric allows; main.rs checks the chosen metric's range.
+pub fn parse_min_contrast(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    let (wcag, apca) = (ContrastMetric::Wcag.valid_range(), ContrastMetric::Apca.valid_range());
+    if value.is_finite() && (wcag.0.min(apca.0)..=wcag.1.max(apca.1)).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("must be a WCAG ratio from {} to {} or an APCA Lc from {} to {}, got {}", wcag.0, wcag.1, apca.0, apca.1, s))
+    }
+}
+
+/// Parses `MIN-MAX` where both ends lie in 0..=1, e.g. '0.6-0.75'.
+pub fn parse_unit_range(s: &str) -> Result<(f64, f64), String> {
+    let (min, max) = s.split_once('-').ok_or_else(|| format!("expected MIN-MAX, got '{}'", s))?;
+    let min = parse_ratio(min.trim())?;
+    let max = parse_ratio(max.trim())?;
+    if min > max {
+        return Err(format!("minimum {} is greater than maximum {}", min, max));
+    }
+    Ok((min, max))
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupsCommand {
+    /// Strictly validate every stored backup and report problems by line number
+    Check,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ScheduleCommand {
+    /// Load the backup scheduled for the current time once (suitable for cron)
+    Apply,
+    /// Keep running and load the scheduled backup whenever a boundary passes
+    Daemon,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ThemesCommand {
+    /// Show the bundled themes with a preview of color0-7
+    List,
+    /// Apply a bundled theme to kitty.conf
+    Apply {
+        /// Theme name as shown by `rtc themes list` (e.g., 'nord')
+        theme: String,
+
+        /// Save the theme as a named backup instead of applying it
+        #[arg(short = 'n', long = "name", value_name = "NAME")]
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Base16Command {
+    /// Import a base16 or base24 scheme YAML file as a backup
+    Import {
+        /// Path to the scheme YAML
+        file: PathBuf,
+
+        /// Backup name to store it under (default: the file name without extension)
+        #[arg(short = 'n', long = "name", value_name = "NAME")]
+        name: Option<String>,
+
+        /// Override where keys come from, as KEY=SLOT pairs (e.g., 'cursor=base0D,c0=base01')
+        #[arg(long = "map", value_name = "MAPPING")]
+        map: Option<String>,
+
+        /// Also apply the imported scheme to kitty.conf
+        #[arg(long = "apply")]
+        apply: bool,
+    },
+    /// Export a backup (or the live kitty.conf) as base16 or base24 YAML
+    Export {
+        /// Backup to export; the live kitty.conf is used when omitted
+        source: Option<String>,
+
+        /// File to write; the YAML is printed when omitted
+        #[arg(short = 'o', long = "output", value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Scheme system to export
+        #[arg(long = "system", value_enum, default_value = "base16")]
+        system: Base16System,
+
+        /// Override which key fills each slot, as KEY=SLOT pairs (e.g., 'c8=base02')
+        #[arg(long = "map", value_name = "MAPPING")]
+        map: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ItermCommand {
+    /// Import an iTerm2 .itermcolors file as a backup
+    Import {
+        /// Path to the .itermcolors file
+        file: PathBuf,
+
+        /// Backup name to store it under (default: the file name without extension)
+        #[arg(short = 'n', long = "name", value_name = "NAME")]
+        name: Option<String>,
+
+        /// Also apply the imported preset to kitty.conf
+        #[arg(long = "apply")]
+        apply: bool,
+    },
+    /// Export a backup (or the live kitty.conf) as an iTerm2 .itermcolors file
+    Export {
+        /// Backup to export; the live kitty.conf is used when omitted
+        source: Option<String>,
+
+        /// File to write; the plist is printed when omitted
+        #[arg(short = 'o', long = "output", value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+}
@@ -0,0 +1,270 @@
+use crate::util::hex_to_rgb;
+
+/// A colour in Björn Ottosson's OKLab space. `l` is roughly 0.0 (black) to 1.0 (white).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+pub fn srgb_to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f64) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let c = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+pub fn rgb_to_hex(r: u8, g: u8, b: u8) -> String {
+    format!("{:02x}{:02x}{:02x}", r, g, b)
+}
+
+impl Oklab {
+    pub fn from_linear_rgb(r: f64, g: f64, b: f64) -> Self {
+        let l = 0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b;
+        let m = 0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b;
+        let s = 0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b;
+
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+        Oklab {
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        }
+    }
+
+    /// Unclamped linear sRGB; components outside 0..1 mean the colour is out of gamut.
+    pub fn to_linear_rgb(self) -> (f64, f64, f64) {
+        let l = self.l + 0.3963377774 * self.a + 0.2158037573 * self.b;
+        let m = self.l - 0.1055613458 * self.a - 0.0638541728 * self.b;
+        let s = self.l - 0.0894841775 * self.a - 1.2914855480 * self.b;
+
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+
+        (
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+        )
+    }
+
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Oklab::from_linear_rgb(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b))
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let (r, g, b) = hex_to_rgb(hex)?;
+        Ok(Oklab::from_rgb(r, g, b))
+    }
+
+    /// Converts back to a 6-digit hex code, clamping out-of-gamut channels.
+    pub fn to_hex(self) -> String {
+        let (r, g, b) = self.to_linear_rgb();
+        rgb_to_hex(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+    }
+
+    /// Linear interpolation; `t = 0.0` gives `self`, `t = 1.0` gives `other`.
+    pub fn lerp(self, other: Oklab, t: f64) -> Oklab {
+        Oklab {
+            l: self.l + (other.l - self.l) * t,
+            a: self.a + (other.a - self.a) * t,
+            b: self.b + (other.b - self.b) * t,
+        }
+    }
+}
+
+/// OKLab in polar form: lightness, chroma and hue in degrees (0..360).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+}
+
+pub fn normalise_hue(h: f64) -> f64 {
+    h.rem_euclid(360.0)
+}
+
+impl From<Oklab> for Oklch {
+    fn from(lab: Oklab) -> Self {
+        Oklch {
+            l: lab.l,
+            c: lab.a.hypot(lab.b),
+            h: normalise_hue(lab.b.atan2(lab.a).to_degrees()),
+        }
+    }
+}
+
+impl From<Oklch> for Oklab {
+    fn from(lch: Oklch) -> Self {
+        let h = lch.h.to_radians();
+        Oklab { l: lch.l, a: lch.c * h.cos(), b: lch.c * h.sin() }
+    }
+}
+
+fn in_srgb_gamut(lab: Oklab) -> bool {
+    let (r, g, b) = lab.to_linear_rgb();
+    let ok = |v: f64| (-1e-4..=1.0 + 1e-4).contains(&v);
+    ok(r) && ok(g) && ok(b)
+}
+
+impl Oklch {
+    pub fn new(l: f64, c: f64, h: f64) -> Self {
+        Oklch { l: l.clamp(0.0, 1.0), c: c.max(0.0), h: normalise_hue(h) }
+    }
+
+    /// Brings the colour into sRGB by reducing chroma while keeping lightness and hue.
+    pub fn gamut_mapped(self) -> Oklch {
+        let l = self.l.clamp(0.0, 1.0);
+        if in_srgb_gamut(Oklch { l, ..self }.into()) {
+            return Oklch { l, ..self };
+        }
+        let (mut lo, mut hi) = (0.0, self.c);
+        for _ in 0..24 {
+            let mid = (lo + hi) / 2.0;
+            if in_srgb_gamut(Oklch { l, c: mid, h: self.h }.into()) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Oklch { l, c: lo, h: self.h }
+    }
+
+    pub fn to_hex(self) -> String {
+        Oklab::from(self.gamut_mapped()).to_hex()
+    }
+}
+
+/// Interpolates two hex colours in OKLab.
+pub fn mix_hex(hex_a: &str, hex_b: &str, t: f64) -> Result<String, String> {
+    let a = Oklab::from_hex(hex_a)?;
+    let b = Oklab::from_hex(hex_b)?;
+    Ok(a.lerp(b, t).to_hex())
+}
+
+/// A colour in CIE L*a*b* (D65 white point).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Lab {
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+
+        let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+        let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+        let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+
+        let f = |t: f64| {
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                (24389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        };
+        let (fx, fy, fz) = (f(x / 0.95047), f(y), f(z / 1.08883));
+
+        Lab {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let (r, g, b) = hex_to_rgb(hex)?;
+        Ok(Lab::from_rgb(r, g, b))
+    }
+}
+
+/// CIEDE2000 colour difference. Around 1.0 is a just-noticeable difference;
+/// above 10 the colours read as clearly different.
+pub fn delta_e_2000(lab1: Lab, lab2: Lab) -> f64 {
+    use std::f64::consts::PI;
+
+    let c1 = lab1.a.hypot(lab1.b);
+    let c2 = lab2.a.hypot(lab2.b);
+    let c_bar = (c1 + c2) / 2.0;
+    let c_bar7 = c_bar.powi(7);
+    let g = 0.5 * (1.0 - (c_bar7 / (c_bar7 + 25f64.powi(7))).sqrt());
+
+    let a1p = (1.0 + g) * lab1.a;
+    let a2p = (1.0 + g) * lab2.a;
+    let c1p = a1p.hypot(lab1.b);
+    let c2p = a2p.hypot(lab2.b);
+
+    let hue = |b: f64, ap: f64| {
+        if b == 0.0 && ap == 0.0 {
+            0.0
+        } else {
+            let h = b.atan2(ap).to_degrees();
+            if h < 0.0 { h + 360.0 } else { h }
+        }
+    };
+    let h1p = hue(lab1.b, a1p);
+    let h2p = hue(lab2.b, a2p);
+
+    let dl = lab2.l - lab1.l;
+    let dc = c2p - c1p;
+    let dh = if c1p * c2p == 0.0 {
+        0.0
+    } else if (h2p - h1p).abs() <= 180.0 {
+        h2p - h1p
+    } else if h2p - h1p > 180.0 {
+        h2p - h1p - 360.0
+    } else {
+        h2p - h1p + 360.0
+    };
+    let d_big_h = 2.0 * (c1p * c2p).sqrt() * (dh.to_radians() / 2.0).sin();
+
+    let l_bar = (lab1.l + lab2.l) / 2.0;
+    let cp_bar = (c1p + c2p) / 2.0;
+    let hp_bar = if c1p * c2p == 0.0 {
+        h1p + h2p
+    } else if (h1p - h2p).abs() <= 180.0 {
+        (h1p + h2p) / 2.0
+    } else if h1p + h2p < 360.0 {
+        (h1p + h2p + 360.0) / 2.0
+    } else {
+        (h1p + h2p - 360.0) / 2.0
+    };
+
+    let t = 1.0 - 0.17 * (hp_bar - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * hp_bar).to_radians().cos()
+        + 0.32 * (3.0 * hp_bar + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * hp_bar - 63.0).to_radians().cos();
+
+    let d_theta = 30.0 * (-((hp_bar - 275.0) / 25.0).powi(2)).exp();
+    let cp_bar7 = cp_bar.powi(7);
+    let r_c = 2.0 * (cp_bar7 / (cp_bar7 + 25f64.powi(7))).sqrt();
+    let l_bar_sq = (l_bar - 50.0).powi(2);
+    let s_l = 1.0 + 0.015 * l_bar_sq / (20.0 + l_bar_sq).sqrt();
+    let s_c = 1.0 + 0.045 * cp_bar;
+    let s_h = 1.0 + 0.015 * cp_bar * t;
+    let r_t = -(2.0 * d_theta * PI / 180.0).sin() * r_c;
+
+    let (tl, tc, th) = (dl / s_l, dc / s_c, d_big_h / s_h);
+    (tl * tl + tc * tc + th * th + r_t * tc * th).sqrt()
+}
+
+/// CIEDE2000 difference between two hex colours.
+pub fn delta_e_hex(hex_a: &str, hex_b: &str) -> Result<f64, String> {
+    Ok(delta_e_2000(Lab::from_hex(hex_a)?, Lab::from_hex(hex_b)?))
+}
@@ -1,11 +1,32 @@
 use std::{collections::HashMap, fs, io, path::PathBuf};
-use crate::config::get_colours_backup_path;
+use crate::colour_space::Oklab;
+use crate::pool::ColourPool;
+use crate::colour_names::nearest_colour_name;
+use crate::cvd::{simulate_hex, CvdType};
+use crate::contrast::{apply_contrast_requirement, failing_keys, ContrastFix, ContrastRequirement, MAX_REGENERATE_ATTEMPTS};
+use crate::generate::{derive_brights, generate_palette, PaletteOptions};
+use crate::config::require_kitty_config_path;
+use crate::backup::{ColourBackup, read_colours_backup, write_colours_backup};
 use crate::util::{generate_random_colour_hex,
                   hex_to_rgb,
-                  inverted_hex_to_rgb,};
+                  inverted_hex_to_rgb,
+                  is_valid_hex_code,
+                  colour_swatch,};
+use rand_chacha::ChaCha8Rng;
 use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use std::sync::LazyLock;
 
+/// kitty colour settings beyond the 19 `COLOUR_KEYS`. They are carried through
+/// backups and imports but are not touched by random, shuffle or the other generators.
+pub const EXTENDED_COLOUR_KEYS: [&str; 18] = [
+    "selection_foreground", "selection_background", "cursor_text_color", "url_color",
+    "active_border_color", "inactive_border_color", "bell_border_color", "visual_bell_color",
+    "active_tab_foreground", "active_tab_background", "inactive_tab_foreground", "inactive_tab_background",
+    "tab_bar_background", "tab_bar_margin_color",
+    "mark1_foreground", "mark1_background", "mark2_foreground", "mark2_background",
+];
+
 pub static COLOUR_KEY_ALIASES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
     let mut m = HashMap::new();
     m.insert("fg", "foreground");
@@ -41,15 +62,28 @@ pub type ColourMap = HashMap<String, String>;
 pub fn extract_current_colours(config_file_path: &PathBuf) -> Result<ColourMap, io::Error> {
     let original_content = fs::read_to_string(config_file_path)
         .map_err(|e| io::Error::new(e.kind(), format!("Failed to read kitty.conf for colour extraction: {}", e)))?;
+    Ok(extract_colours_from_content(&original_content, &COLOUR_KEYS))
+}
+
+/// Like `extract_current_colours`, but for the `EXTENDED_COLOUR_KEYS`.
+pub fn extract_extended_colours(config_file_path: &PathBuf) -> Result<ColourMap, io::Error> {
+    let original_content = fs::read_to_string(config_file_path)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to read kitty.conf for colour extraction: {}", e)))?;
+    Ok(extract_colours_from_content(&original_content, &EXTENDED_COLOUR_KEYS))
+}
+
+/// Reads `key #rrggbb` lines for the given keys from kitty.conf-style content.
+/// Comments and values that are not 6-digit hex codes (such as `none`) are skipped.
+pub fn extract_colours_from_content(content: &str, keys: &[&str]) -> ColourMap {
     let mut current_colours: ColourMap = HashMap::new();
 
-    for line in original_content.lines() {
+    for line in content.lines() {
         let trimmed_line = line.trim();
         if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
             continue;
         }
 
-        for &key in COLOUR_KEYS.iter() {
+        for &key in keys.iter() {
             if trimmed_line.starts_with(key) {
                 let end_of_key_idx = key.len();
                 let remaining_line = &trimmed_line[end_of_key_idx..];
@@ -60,7 +94,7 @@ pub fn extract_current_colours(config_file_path: &PathBuf) -> Result<ColourMap,
                     let chars_between_key_and_hash = &remaining_line[..hash_pos];
                     if chars_between_key_and_hash.trim().is_empty() {
                         let hex_code = remaining_line[hash_pos + 1..].trim();
-                        if hex_code.len() == 6 && hex_code.chars().all(|c| c.is_ascii_hexdigit()) {
+                        if is_valid_hex_code(hex_code) {
                             current_colours.insert(key.to_string(), hex_code.to_string());
                             break;
                         }
@@ -69,7 +103,7 @@ pub fn extract_current_colours(config_file_path: &PathBuf) -> Result<ColourMap,
             }
         }
     }
-    Ok(current_colours)
+    current_colours
 }
 
 pub fn update_kitty_config_with_colours(config_file_path: &PathBuf, colours_to_apply: &ColourMap) -> Result<(), io::Error> {
@@ -112,51 +146,87 @@ pub fn update_kitty_config_with_colours(config_file_path: &PathBuf, colours_to_a
     Ok(())
 }
 
-pub fn create_colours_backup(config_file_path: &PathBuf, backup_name: Option<String>) -> Result<(), io::Error> {
+pub fn create_colours_backup(
+    config_file_path: &PathBuf,
+    backup_name: Option<String>,
+    description: Option<String>,
+    tags_input: &Option<String>,
+) -> Result<(), io::Error> {
     if !config_file_path.exists() {
         eprintln!("Error: kitty.conf not found at {}. Cannot create colour backup.", config_file_path.display());
         return Err(io::Error::new(io::ErrorKind::NotFound, "kitty.conf not found"));
     }
 
     let current_colours = extract_current_colours(config_file_path)?;
-    let backup_file_path = get_colours_backup_path(&backup_name)?;
-
-    let mut backup_content = String::new();
     for &key in COLOUR_KEYS.iter() {
-        if let Some(colour_hex) = current_colours.get(key) {
-            backup_content.push_str(&format!("{}#{}\n", key, colour_hex));
-        } else {
-            eprintln!("Warning: Colour key '{}' not found in current kitty.conf for backup. Backing up with default/missing value.", key);
-            backup_content.push_str(&format!("{}#000000\n", key));
+        if !current_colours.contains_key(key) {
+            eprintln!("Warning: Colour key '{}' not found in current kitty.conf for backup. It will be recorded as unset.", key);
         }
     }
 
-    println!("Creating colour backup to: {}", backup_file_path.display());
-    fs::write(&backup_file_path, backup_content)
-        .map_err(|e| io::Error::new(e.kind(), format!("Failed to write colour backup: {}", e)))?;
+    let tags: Vec<String> = tags_input.as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let mut backup = ColourBackup::new(current_colours, Some(config_file_path.as_path()), description, tags);
+    backup.extended = extract_extended_colours(config_file_path)?;
+    write_colours_backup(&backup_name, &backup)?;
 
     println!("Colour backup created successfully!");
     Ok(())
 }
 
-pub fn load_colours_from_backup(config_file_path: &PathBuf, backup_name: Option<String>) -> Result<(), io::Error> {
-    let backup_file_path = get_colours_backup_path(&backup_name)?;
+pub fn load_colours_from_backup(
+    config_file_path: &PathBuf,
+    backup_name: Option<String>,
+    exception_keys_input: &Option<String>,
+    force_keys_input: &Option<String>,
+) -> Result<(), io::Error> {
+    let backup = read_colours_backup(&backup_name)?;
 
-    if !backup_file_path.exists() {
-        eprintln!("Error: Colour backup file not found at {}. Cannot load.", backup_file_path.display());
-        return Err(io::Error::new(io::ErrorKind::NotFound, "Colour backup file not found"));
+    if backup.format_version == 0 {
+        println!("Backup uses the legacy key#hex format; re-save it with `rtc -b` to upgrade.");
+    }
+    if let Some(created) = &backup.created {
+        println!("Backup created {} from {}.", created, backup.source_config.as_deref().unwrap_or("an unknown config"));
+    }
+    if let Some(description) = &backup.description {
+        println!("Description: {}", description);
+    }
+    if !backup.tags.is_empty() {
+        println!("Tags: {}", backup.tags.join(", "));
     }
 
-    let backup_content = fs::read_to_string(&backup_file_path)
-        .map_err(|e| io::Error::new(e.kind(), format!("Failed to read colour backup: {}", e)))?;
-    let mut colours_to_apply = HashMap::new();
+    let forced_keys = parse_forced_keys_input(force_keys_input)?;
+    let excluded_keys = parse_colour_keys_input(exception_keys_input);
 
-    for line in backup_content.lines() {
-        if let Some(hash_pos) = line.find('#') {
-            let key = line[0..hash_pos].trim();
-            let hex = line[hash_pos + 1..].trim();
-            colours_to_apply.insert(key.to_string(), hex.to_string());
-        }
+    let unset_keys: Vec<&str> = backup.unset_keys()
+        .into_iter()
+        .filter(|key| is_key_selected(key, &forced_keys, &excluded_keys))
+        .collect();
+    if !unset_keys.is_empty() {
+        println!("Keys unset in this backup (left unchanged): {}", unset_keys.join(", "));
+    }
+
+    let colours_to_apply: ColourMap = backup.colours
+        .into_iter()
+        .chain(backup.extended)
+        .filter(|(key, _)| is_key_selected(key, &forced_keys, &excluded_keys))
+        .collect();
+    if colours_to_apply.is_empty() {
+        eprintln!("Warning: None of the selected colour keys have a value in this backup. No changes applied.");
+        return Ok(());
+    }
+    if !forced_keys.is_empty() || !excluded_keys.is_empty() {
+        let restored: Vec<&str> = COLOUR_KEYS.iter()
+            .chain(EXTENDED_COLOUR_KEYS.iter())
+            .copied()
+            .filter(|key| colours_to_apply.contains_key(*key))
+            .collect();
+        println!("Restoring only: {}", restored.join(", "));
     }
 
     println!("Loading colours from backup: {}", config_file_path.display());
@@ -167,6 +237,34 @@ pub fn load_colours_from_backup(config_file_path: &PathBuf, backup_name: Option<
     Ok(())
 }
 
+/// Whether `key` is affected by an operation given the `-f` (only these) and `-e`
+/// (all but these) key lists. `-f` wins when both are non-empty.
+pub fn is_key_selected(key: &str, forced_keys: &[String], excluded_keys: &[String]) -> bool {
+    if !forced_keys.is_empty() {
+        forced_keys.iter().any(|k| k == key)
+    } else {
+        !excluded_keys.iter().any(|k| k == key)
+    }
+}
+
+pub fn resolve_colour_key(key_or_alias: &str) -> Option<&'static str> {
+    if let Some(&full_key) = COLOUR_KEY_ALIASES.get(key_or_alias) {
+        Some(full_key)
+    } else {
+        COLOUR_KEYS.iter().copied().find(|&k| k == key_or_alias)
+    }
+}
+
+pub fn ansi_colour_index(key: &str) -> Option<usize> {
+    key.strip_prefix("color").and_then(|n| n.parse().ok())
+}
+
+/// Seeds drive `ChaCha8Rng`, whose output is the same on every platform, so a
+/// printed seed reproduces its result on any machine running the same rtc version.
+fn print_seed(seed: u64) {
+    println!("Seed: {} (re-run with `--seed {}` and the same options on this rtc version to reproduce this result)", seed, seed);
+}
+
 pub fn parse_colour_keys_input(input: &Option<String>) -> Vec<String> {
     let mut result_keys = Vec::new();
     if let Some(s) = input {
@@ -177,6 +275,19 @@ pub fn parse_colour_keys_input(input: &Option<String>) -> Vec<String> {
                 continue;
             }
 
+            // Ranges of ANSI colours, e.g. `c0-c7` or `color8-color15`.
+            if let Some((start, end)) = trimmed_part.split_once('-') {
+                let start_idx = resolve_colour_key(start.trim()).and_then(ansi_colour_index);
+                let end_idx = resolve_colour_key(end.trim()).and_then(ansi_colour_index);
+                match (start_idx, end_idx) {
+                    (Some(a), Some(b)) if a <= b => {
+                        result_keys.extend((a..=b).map(|i| format!("color{}", i)));
+                    }
+                    _ => eprintln!("Warning: Invalid colour key range '{}' provided in list (expected e.g. 'c0-c7'). It will be ignored.", trimmed_part),
+                }
+                continue;
+            }
+
             if let Some(&full_key) = COLOUR_KEY_ALIASES.get(trimmed_part) {
                 result_keys.push(full_key.to_string());
             } else if COLOUR_KEYS.contains(&trimmed_part) {
@@ -189,28 +300,66 @@ pub fn parse_colour_keys_input(input: &Option<String>) -> Vec<String> {
     result_keys
 }
 
+/// Parses a `-f` list like `parse_colour_keys_input`, but fails when keys were
+/// given and none of them is recognised: an empty list would otherwise mean "every
+/// key", so a single typo must not widen the operation to the whole scheme.
+pub fn parse_forced_keys_input(input: &Option<String>) -> Result<Vec<String>, io::Error> {
+    let forced_keys = parse_colour_keys_input(input);
+    let requested = input.as_deref()
+        .is_some_and(|s| s.trim_start_matches('(').trim_end_matches(')').split(',').any(|part| !part.trim().is_empty()));
+    if requested && forced_keys.is_empty() {
+        eprintln!("Error: None of the keys given with --force (-f) are valid colour keys. No changes applied.");
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "No valid --force keys"));
+    }
+    Ok(forced_keys)
+}
+
+/// Everything `-r` needs beyond the key filters.
+pub struct RandomColourOptions {
+    pub palette: PaletteOptions,
+    pub derive_brights_amount: Option<f64>,
+    pub contrast: Option<ContrastRequirement>,
+    pub seed: u64,
+    /// Draw colours from this pool instead of generating them.
+    pub pool: Option<ColourPool>,
+    /// Use each pool colour at most once.
+    pub without_replacement: bool,
+}
+
 pub fn apply_random_colours_to_kitty(
     config_file_path: &PathBuf,
     exception_keys_input: &Option<String>,
     force_keys_input: &Option<String>,
+    options: &RandomColourOptions,
 ) -> Result<(), io::Error> {
     let current_colours = extract_current_colours(config_file_path)?;
     let mut generated_colours_map: ColourMap = HashMap::new();
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
 
-    let forced_keys = parse_colour_keys_input(force_keys_input);
+    let forced_keys = parse_forced_keys_input(force_keys_input)?;
     let excluded_keys = parse_colour_keys_input(exception_keys_input);
+    let selected_keys: Vec<String> = COLOUR_KEYS.iter()
+        .filter(|key| is_key_selected(key, &forced_keys, &excluded_keys))
+        .map(|key| key.to_string())
+        .collect();
+
+    let draw_from_pool = |pool: &ColourPool, rng: &mut ChaCha8Rng| {
+        pool.draw(&selected_keys, !options.without_replacement, rng).map_err(|e| {
+            eprintln!("Error: Cannot draw from the colour pool: {}. No changes applied.", e);
+            io::Error::new(io::ErrorKind::InvalidInput, "Colour pool too small")
+        })
+    };
+    let mut palette = match &options.pool {
+        Some(pool) => draw_from_pool(pool, &mut rng)?,
+        None => generate_palette(&options.palette, &mut rng),
+    };
 
     for &key in COLOUR_KEYS.iter() {
         let key_string = key.to_string();
 
-        let should_randomize = if !forced_keys.is_empty() {
-            forced_keys.contains(&key_string)
-        } else {
-            !excluded_keys.contains(&key_string)
-        };
-
-        if should_randomize {
-            generated_colours_map.insert(key_string, generate_random_colour_hex());
+        if is_key_selected(key, &forced_keys, &excluded_keys) {
+            let hex = palette.remove(key).unwrap_or_else(|| generate_random_colour_hex(&mut rng));
+            generated_colours_map.insert(key_string, hex);
         } else {
             if let Some(current_hex) = current_colours.get(key) {
                 generated_colours_map.insert(key_string, current_hex.clone());
@@ -221,18 +370,135 @@ pub fn apply_random_colours_to_kitty(
         }
     }
 
+    if let Some(requirement) = &options.contrast
+        && requirement.fix == ContrastFix::Regenerate
+    {
+        for _ in 0..MAX_REGENERATE_ATTEMPTS {
+            let failing: Vec<String> = failing_keys(&generated_colours_map, requirement)
+                .into_iter()
+                .map(|(key, _)| key)
+                .filter(|key| selected_keys.contains(key))
+                .collect();
+            if failing.is_empty() {
+                break;
+            }
+            // Without replacement a partial redraw could repeat a colour, so the whole
+            // selection is drawn again.
+            if let Some(pool) = &options.pool
+                && options.without_replacement
+            {
+                generated_colours_map.extend(draw_from_pool(pool, &mut rng)?);
+                continue;
+            }
+            let mut fresh_palette = match &options.pool {
+                Some(pool) => draw_from_pool(pool, &mut rng)?,
+                None => generate_palette(&options.palette, &mut rng),
+            };
+            for key in failing {
+                if let Some(hex) = fresh_palette.remove(&key) {
+                    generated_colours_map.insert(key, hex);
+                }
+            }
+        }
+    }
+
+    if let Some(amount) = options.derive_brights_amount {
+        generated_colours_map.extend(derive_brights(&generated_colours_map, amount, &forced_keys, &excluded_keys));
+    }
+
+    if let Some(requirement) = &options.contrast {
+        apply_contrast_requirement(&mut generated_colours_map, &selected_keys, requirement)?;
+    }
+
     println!("\nGenerated new random colours:");
     update_kitty_config_with_colours(config_file_path, &generated_colours_map)?;
 
     println!("\nKitty colours updated in config file!");
     println!("Please restart Kitty manually to see the changes, as live reload is not reliably supported by your Kitty version.");
+    print_seed(options.seed);
     Ok(())
 }
 
-pub fn print_current_colours_to_terminal(config_file_path: &PathBuf) -> Result<(), io::Error> {
+pub fn derive_brights_in_kitty(
+    config_file_path: &PathBuf,
+    exception_keys_input: &Option<String>,
+    force_keys_input: &Option<String>,
+    amount: f64,
+) -> Result<(), io::Error> {
     let current_colours = extract_current_colours(config_file_path)?;
+    let forced_keys = parse_forced_keys_input(force_keys_input)?;
+    let excluded_keys = parse_colour_keys_input(exception_keys_input);
+
+    let derived = derive_brights(&current_colours, amount, &forced_keys, &excluded_keys);
+    if derived.is_empty() {
+        eprintln!("Warning: No bright colours selected for derivation. No changes applied.");
+        return Ok(());
+    }
+
+    println!("\nDerived bright colours (amount {:.2}):", amount);
+    for &key in COLOUR_KEYS.iter() {
+        if let Some(hex) = derived.get(key) {
+            println!("  {}: #{}", key, hex);
+        }
+    }
+    update_kitty_config_with_colours(config_file_path, &derived)?;
 
-    println!(" ┏━━━━━━━━━━━━━━━━━━━━━━┓ ");
+    println!("\nKitty colours updated in config file!");
+    println!("Please restart Kitty manually to see the changes, as live reload is not reliably supported by your Kitty version.");
+    Ok(())
+}
+
+/// Saves `colours` as the named backup when `backup_name` is given, otherwise writes
+/// them to kitty.conf.
+pub fn apply_or_save_colours(
+    colours: &ColourMap,
+    backup_name: Option<String>,
+    description: String,
+    tags: Vec<String>,
+) -> Result<(), io::Error> {
+    for &key in COLOUR_KEYS.iter() {
+        if let Some(hex) = colours.get(key) {
+            println!("  {:<11} {} #{}", key, colour_swatch(hex), hex);
+        }
+    }
+
+    if let Some(name) = &backup_name {
+        let backup = ColourBackup::new(colours.clone(), None, Some(description), tags);
+        write_colours_backup(&backup_name, &backup)?;
+        println!("Saved as backup '{}'. Load it with `rtc -l -n {}`.", name, name);
+        return Ok(());
+    }
+
+    let config_file_path = require_kitty_config_path()?;
+    update_kitty_config_with_colours(&config_file_path, colours)?;
+
+    println!("\nKitty colours updated in config file!");
+    println!("Please restart Kitty manually to see the changes, as live reload is not reliably supported by your Kitty version.");
+    Ok(())
+}
+
+/// Width of the `≈ name` column in `-g` output, including its leading space; fits
+/// the longest colour name.
+const NAME_COLUMN_WIDTH: usize = 23;
+
+/// `= name` for an exact named colour, `≈ name` for the nearest one otherwise.
+fn colour_name_label(hex: &str) -> String {
+    match nearest_colour_name(hex) {
+        Ok((name, delta)) if delta < 0.5 => format!("= {}", name),
+        Ok((name, _)) => format!("≈ {}", name),
+        Err(_) => String::new(),
+    }
+}
+
+pub fn print_current_colours_to_terminal(config_file_path: &PathBuf, simulate: Option<CvdType>) -> Result<(), io::Error> {
+    let current_colours = extract_current_colours(config_file_path)?;
+
+    if let Some(cvd) = simulate {
+        println!("Swatches simulate {}; hex codes are the configured values.", cvd.name());
+    }
+
+    let border = "━".repeat(22 + NAME_COLUMN_WIDTH);
+    println!(" ┏{}┓ ", border);
     for &key in COLOUR_KEYS.iter() {
         let num_spaces = match key {
             "foreground" | "background" => 2,
@@ -244,12 +510,17 @@ pub fn print_current_colours_to_terminal(config_file_path: &PathBuf) -> Result<(
         let spacing = " ".repeat(num_spaces);
 
         if let Some(colour_hex) = current_colours.get(key) {
-            match inverted_hex_to_rgb(colour_hex) {
+            let display_hex = match simulate {
+                Some(cvd) => simulate_hex(colour_hex, cvd).unwrap_or_else(|_| colour_hex.clone()),
+                None => colour_hex.clone(),
+            };
+            match inverted_hex_to_rgb(&display_hex) {
                 Ok((r, g, b)) => {
-                    let (inv_r, inv_g, inv_b) = hex_to_rgb(colour_hex).unwrap_or((0, 0, 0));
+                    let (inv_r, inv_g, inv_b) = hex_to_rgb(&display_hex).unwrap_or((0, 0, 0));
                     println!(
-                        " ┃ {}:{}\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m#{}\x1b[0m ┃ ",
-                        key, spacing, r, g, b, inv_r, inv_g, inv_b, colour_hex
+                        " ┃ {}:{}\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m#{}\x1b[0m {:<width$} ┃ ",
+                        key, spacing, r, g, b, inv_r, inv_g, inv_b, colour_hex, colour_name_label(colour_hex),
+                        width = NAME_COLUMN_WIDTH - 1
                     );
                 }
                 Err(e) => {
@@ -261,21 +532,88 @@ pub fn print_current_colours_to_terminal(config_file_path: &PathBuf) -> Result<(
             println!("    {}:{} (Not found in config, defaulting to #000000)", key, spacing);
         }
     }
-    println!(" ┗━━━━━━━━━━━━━━━━━━━━━━┛ ");
+    println!(" ┗{}┛ ", border);
     println!("> {}", config_file_path.display());
     Ok(())
 }
 
+/// Everything `-s` needs beyond the key filters.
+pub struct ShuffleOptions {
+    pub seed: u64,
+    pub contrast: Option<ContrastRequirement>,
+    /// Only swap values among the special keys (foreground, background, cursor),
+    /// among color0-7 and among color8-15.
+    pub within_groups: bool,
+    /// Reject permutations that flip whether the background is darker than the foreground.
+    pub keep_bg_fg_order: bool,
+    /// Every shuffled key must end up with a different value than it had.
+    pub derangement: bool,
+}
+
+/// Splits the shuffled keys into the groups values may move between: one group
+/// for everything, or special/normal/bright groups with `within_groups`.
+fn shuffle_groups(keys: &[String], within_groups: bool) -> Vec<Vec<usize>> {
+    if !within_groups {
+        return vec![(0..keys.len()).collect()];
+    }
+    let group_of = |key: &str| match ansi_colour_index(key) {
+        Some(i) if i < 8 => 1,
+        Some(_) => 2,
+        None => 0,
+    };
+    (0..3)
+        .map(|group| (0..keys.len()).filter(|&i| group_of(&keys[i]) == group).collect::<Vec<usize>>())
+        .filter(|group| !group.is_empty())
+        .collect()
+}
+
+/// Shuffles `values` in place within each group. With `derangement`, each group is
+/// re-shuffled until none of its keys keeps its old value.
+fn shuffle_within_groups(
+    values: &mut [String],
+    groups: &[Vec<usize>],
+    derangement: bool,
+    rng: &mut ChaCha8Rng,
+) -> Result<(), String> {
+    for group in groups {
+        let original: Vec<String> = group.iter().map(|&i| values[i].clone()).collect();
+        let mut shuffled = original.clone();
+        let mut attempts = 0;
+        loop {
+            shuffled.shuffle(rng);
+            attempts += 1;
+            if !derangement || shuffled.iter().zip(&original).all(|(new, old)| new != old) {
+                break;
+            }
+            if attempts >= MAX_REGENERATE_ATTEMPTS {
+                return Err(format!("no arrangement changes every key in a group of {} (values repeat or the group is too small)", group.len()));
+            }
+        }
+        for (&i, hex) in group.iter().zip(shuffled) {
+            values[i] = hex;
+        }
+    }
+    Ok(())
+}
+
+/// Whether the background is darker than the foreground, by OKLab lightness.
+fn is_background_darker(colours: &ColourMap) -> Option<bool> {
+    let bg = Oklab::from_hex(colours.get("background")?).ok()?;
+    let fg = Oklab::from_hex(colours.get("foreground")?).ok()?;
+    Some(bg.l < fg.l)
+}
+
 pub fn shuffle_current_colours(
     config_file_path: &PathBuf,
     exception_keys_input: &Option<String>,
     force_keys_input: &Option<String>,
+    options: &ShuffleOptions,
 ) -> Result<(), io::Error> {
     println!("Shuffling current colours...");
 
     let current_colours_map = extract_current_colours(config_file_path)?;
 
-    let forced_keys = parse_colour_keys_input(force_keys_input);
+    let forced_keys = parse_forced_keys_input(force_keys_input)?;
     let excluded_keys = parse_colour_keys_input(exception_keys_input);
 
     let mut shufflable_keys_full_names: Vec<String> = Vec::new();
@@ -284,13 +622,7 @@ pub fn shuffle_current_colours(
     for &key in COLOUR_KEYS.iter() {
         let key_string = key.to_string();
 
-        let should_be_shuffled = if !forced_keys.is_empty() {
-            forced_keys.contains(&key_string)
-        } else {
-            !excluded_keys.contains(&key_string)
-        };
-
-        if should_be_shuffled {
+        if is_key_selected(key, &forced_keys, &excluded_keys) {
             if let Some(_colour_hex) = current_colours_map.get(key) {
                 shufflable_keys_full_names.push(key_string);
             } else {
@@ -311,40 +643,58 @@ pub fn shuffle_current_colours(
         return Ok(());
     }
 
-    let mut shufflable_hex_values: Vec<String> = shufflable_keys_full_names
+    let original_hex_values: Vec<String> = shufflable_keys_full_names
         .iter()
         .map(|key_full_name| {
             current_colours_map.get(key_full_name).cloned().unwrap_or_else(|| "000000".to_string())
         })
         .collect();
 
-
-    let mut rng = rand::rng();
-    shufflable_hex_values.shuffle(&mut rng);
-
-    let mut shuffled_colours_map: ColourMap = HashMap::new();
-    let mut shufflable_idx = 0;
-
-    for &key in COLOUR_KEYS.iter() {
-        let key_string = key.to_string();
-
-        if fixed_colours_map.contains_key(&key_string) {
-            shuffled_colours_map.insert(key_string.clone(), fixed_colours_map[&key_string].clone());
-        } else {
-            if shufflable_idx < shufflable_hex_values.len() {
-                 shuffled_colours_map.insert(key_string, shufflable_hex_values[shufflable_idx].clone());
-                 shufflable_idx += 1;
-            } else {
-                eprintln!("Internal Warning: Missing shuffled value for key '{}'. Defaulting to #000000.", key);
-                shuffled_colours_map.insert(key_string, "000000".to_string());
-            }
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    let build_shuffled_map = |values: &[String]| -> ColourMap {
+        let mut map = fixed_colours_map.clone();
+        for (key, hex) in shufflable_keys_full_names.iter().zip(values) {
+            map.insert(key.clone(), hex.clone());
         }
+        map
+    };
+
+    let groups = shuffle_groups(&shufflable_keys_full_names, options.within_groups);
+    let required_order = if options.keep_bg_fg_order { is_background_darker(&current_colours_map) } else { None };
+    let regenerate_for_contrast = options.contrast.as_ref().filter(|r| r.fix == ContrastFix::Regenerate);
+    let is_acceptable = |map: &ColourMap| {
+        required_order.is_none_or(|darker| is_background_darker(map) == Some(darker))
+            && regenerate_for_contrast.is_none_or(|r| failing_keys(map, r).is_empty())
+    };
+
+    let mut shuffled_colours_map = ColourMap::new();
+    for _ in 0..MAX_REGENERATE_ATTEMPTS {
+        let mut values = original_hex_values.clone();
+        shuffle_within_groups(&mut values, &groups, options.derangement, &mut rng).map_err(|e| {
+            eprintln!("Error: --derangement cannot be satisfied: {}. No changes applied.", e);
+            io::Error::new(io::ErrorKind::InvalidInput, "Derangement impossible")
+        })?;
+        shuffled_colours_map = build_shuffled_map(&values);
+
+        if is_acceptable(&shuffled_colours_map) {
+            break;
+        }
+    }
+    // Remaining contrast failures are adjusted below, but a flipped order cannot be.
+    if required_order.is_some_and(|darker| is_background_darker(&shuffled_colours_map) != Some(darker)) {
+        eprintln!("Error: No shuffle kept the background/foreground lightness order after {} attempts. No changes applied.", MAX_REGENERATE_ATTEMPTS);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Lightness order not kept"));
+    }
+
+    if let Some(requirement) = &options.contrast {
+        apply_contrast_requirement(&mut shuffled_colours_map, &shufflable_keys_full_names, requirement)?;
     }
 
     update_kitty_config_with_colours(config_file_path, &shuffled_colours_map)?;
 
     println!("\nKitty colours shuffled and updated in config file!");
     println!("Please restart Kitty manually to see the changes, as live reload is not reliably supported by your Kitty version.");
+    print_seed(options.seed);
 
     Ok(())
 }
\ No newline at end of file
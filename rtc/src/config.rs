@@ -1,5 +1,4 @@
 use std::{fs, io, path::PathBuf};
-use dirs;
 
 pub fn find_kitty_config_path() -> Option<PathBuf> {
     if let Some(mut path) = dirs::config_dir() {
@@ -19,6 +18,16 @@ pub fn find_kitty_config_path() -> Option<PathBuf> {
     None
 }
 
+pub fn require_kitty_config_path() -> Result<PathBuf, io::Error> {
+    match find_kitty_config_path() {
+        Some(path) => Ok(path),
+        None => {
+            eprintln!("Error: kitty.conf not found. Please ensure it's in ~/.config/kitty/kitty.conf or ~/.kitty.kitty.conf");
+            Err(io::Error::new(io::ErrorKind::NotFound, "kitty.conf not found"))
+        }
+    }
+}
+
 pub fn get_rtc_config_dir() -> Result<PathBuf, io::Error> {
     let mut path = dirs::config_dir()
         .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find config directory"))?;
@@ -38,3 +47,26 @@ pub fn get_colours_backup_path(backup_name: &Option<String>) -> Result<PathBuf,
     path.push(filename);
     Ok(path)
 }
+
+pub fn get_schedule_path() -> Result<PathBuf, io::Error> {
+    let mut path = get_rtc_config_dir()?;
+    path.push("schedule");
+    Ok(path)
+}
+
+pub fn list_colour_backup_names() -> Result<Vec<String>, io::Error> {
+    let dir = get_rtc_config_dir()?;
+    let mut names = Vec::new();
+    for entry in fs::read_dir(&dir)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to read RTC config directory: {}", e)))?
+    {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "rtc_colours")
+            && let Some(stem) = path.file_stem()
+        {
+            names.push(stem.to_string_lossy().into_owned());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
@@ -0,0 +1,200 @@
+use std::io;
+use crate::colour_space::{srgb_to_linear, Oklab, Oklch};
+use crate::colours::ColourMap;
+use crate::util::hex_to_rgb;
+use clap::ValueEnum;
+
+/// Keys whose readability against the background is enforced. color0 is left out
+/// because it conventionally sits right next to the background.
+pub const CONTRAST_CHECKED_KEYS: [&str; 16] = [
+    "foreground",
+    "color1", "color2", "color3", "color4", "color5", "color6", "color7",
+    "color8", "color9", "color10", "color11", "color12", "color13", "color14", "color15",
+];
+
+/// How many times `regenerate` re-rolls failing colours before falling back to `adjust`.
+pub const MAX_REGENERATE_ATTEMPTS: usize = 200;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContrastMetric {
+    /// WCAG 2.x contrast ratio, 1 to 21 (4.5 is the AA threshold for body text)
+    Wcag,
+    /// APCA lightness contrast |Lc|, 0 to ~106 (60 is a common body-text minimum)
+    Apca,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContrastFix {
+    /// Abort without changing kitty.conf
+    Reject,
+    /// Re-roll failing colours (-r) or re-shuffle (-s), then adjust whatever still fails
+    Regenerate,
+    /// Move the lightness of failing colours away from the background in OKLCH
+    Adjust,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ContrastRequirement {
+    pub min_contrast: f64,
+    pub metric: ContrastMetric,
+    pub fix: ContrastFix,
+}
+
+/// WCAG 2.x relative luminance of a 6-digit hex colour.
+pub fn relative_luminance(hex: &str) -> Result<f64, String> {
+    let (r, g, b) = hex_to_rgb(hex)?;
+    Ok(0.2126 * srgb_to_linear(r) + 0.7152 * srgb_to_linear(g) + 0.0722 * srgb_to_linear(b))
+}
+
+pub fn wcag_contrast_ratio(hex_a: &str, hex_b: &str) -> Result<f64, String> {
+    let la = relative_luminance(hex_a)?;
+    let lb = relative_luminance(hex_b)?;
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    Ok((lighter + 0.05) / (darker + 0.05))
+}
+
+/// APCA (0.0.98G-4g) lightness contrast of `text_hex` on `background_hex`, as |Lc|.
+pub fn apca_contrast(text_hex: &str, background_hex: &str) -> Result<f64, String> {
+    let screen_luminance = |hex: &str| -> Result<f64, String> {
+        let (r, g, b) = hex_to_rgb(hex)?;
+        let c = |v: u8| (v as f64 / 255.0).powf(2.4);
+        let y = 0.2126729 * c(r) + 0.7151522 * c(g) + 0.0721750 * c(b);
+        Ok(if y < 0.022 { y + (0.022 - y).powf(1.414) } else { y })
+    };
+    let text = screen_luminance(text_hex)?;
+    let background = screen_luminance(background_hex)?;
+
+    if (background - text).abs() < 0.0005 {
+        return Ok(0.0);
+    }
+    let lc = if background > text {
+        let s = (background.powf(0.56) - text.powf(0.57)) * 1.14;
+        if s < 0.1 { 0.0 } else { s - 0.027 }
+    } else {
+        let s = (background.powf(0.65) - text.powf(0.62)) * 1.14;
+        if s > -0.1 { 0.0 } else { s + 0.027 }
+    };
+    Ok((lc * 100.0).abs())
+}
+
+pub fn contrast(text_hex: &str, background_hex: &str, metric: ContrastMetric) -> Result<f64, String> {
+    match metric {
+        ContrastMetric::Wcag => wcag_contrast_ratio(text_hex, background_hex),
+        ContrastMetric::Apca => apca_contrast(text_hex, background_hex),
+    }
+}
+
+impl ContrastMetric {
+    /// The values a minimum can meaningfully take: WCAG ratios run from 1 to 21,
+    /// APCA |Lc| from 0 to about 106, rounded up to 108.
+    pub fn valid_range(self) -> (f64, f64) {
+        match self {
+            ContrastMetric::Wcag => (1.0, 21.0),
+            ContrastMetric::Apca => (0.0, 108.0),
+        }
+    }
+}
+
+pub fn format_contrast(value: f64, metric: ContrastMetric) -> String {
+    match metric {
+        ContrastMetric::Wcag => format!("{:.2}:1", value),
+        ContrastMetric::Apca => format!("Lc {:.1}", value),
+    }
+}
+
+/// Checked keys in `colours` whose contrast against the background is below the
+/// minimum, with their current contrast.
+pub fn failing_keys(colours: &ColourMap, requirement: &ContrastRequirement) -> Vec<(String, f64)> {
+    let Some(background) = colours.get("background") else {
+        return Vec::new();
+    };
+    CONTRAST_CHECKED_KEYS.iter()
+        .filter_map(|&key| {
+            let hex = colours.get(key)?;
+            let value = contrast(hex, background, requirement.metric).ok()?;
+            (value < requirement.min_contrast).then(|| (key.to_string(), value))
+        })
+        .collect()
+}
+
+/// Moves `hex` away from the background's lightness in OKLCH, keeping hue and
+/// chroma where the gamut allows, until it meets the requirement. Returns the
+/// best candidate found even if the minimum is unreachable.
+pub fn adjust_for_contrast(hex: &str, background_hex: &str, requirement: &ContrastRequirement) -> Result<String, String> {
+    let start: Oklch = Oklab::from_hex(hex)?.into();
+    let background: Oklch = Oklab::from_hex(background_hex)?.into();
+    let preferred_step = if background.l < 0.6 { 0.01 } else { -0.01 };
+
+    let mut best = (hex.to_string(), contrast(hex, background_hex, requirement.metric)?);
+    for step in [preferred_step, -preferred_step] {
+        let mut l = start.l;
+        while (0.0..=1.0).contains(&l) {
+            l += step;
+            let candidate = Oklch::new(l, start.c, start.h).to_hex();
+            let value = contrast(&candidate, background_hex, requirement.metric)?;
+            if value >= requirement.min_contrast {
+                return Ok(candidate);
+            }
+            if value > best.1 {
+                best = (candidate, value);
+            }
+        }
+    }
+    Ok(best.0)
+}
+
+/// Enforces `requirement` on `colours`. Only `adjustable_keys` are modified; other
+/// failing keys are reported as warnings. With `ContrastFix::Reject` nothing is
+/// modified and an error is returned if any of `adjustable_keys` fails, or any key
+/// at all when the background itself is being changed.
+pub fn apply_contrast_requirement(
+    colours: &mut ColourMap,
+    adjustable_keys: &[String],
+    requirement: &ContrastRequirement,
+) -> Result<(), io::Error> {
+    let failing = failing_keys(colours, requirement);
+    if failing.is_empty() {
+        println!("All colours meet the minimum contrast of {} against the background.", format_contrast(requirement.min_contrast, requirement.metric));
+        return Ok(());
+    }
+
+    let warn_untouched = |key: &str, value: f64| {
+        eprintln!("Warning: '{}' ({}) is below the minimum contrast but is not being changed by this operation.", key, format_contrast(value, requirement.metric));
+    };
+
+    if requirement.fix == ContrastFix::Reject {
+        let background_changed = adjustable_keys.iter().any(|key| key == "background");
+        let (rejected, untouched): (Vec<_>, Vec<_>) = failing.into_iter()
+            .partition(|(key, _)| background_changed || adjustable_keys.contains(key));
+        if rejected.is_empty() {
+            for (key, value) in untouched {
+                warn_untouched(&key, value);
+            }
+            return Ok(());
+        }
+        eprintln!("Error: These colours fall below the minimum contrast of {} against the background:", format_contrast(requirement.min_contrast, requirement.metric));
+        for (key, value) in &rejected {
+            eprintln!("  {:<11} #{} ({})", key, colours[key], format_contrast(*value, requirement.metric));
+        }
+        eprintln!("No changes applied.");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Minimum contrast not met"));
+    }
+
+    let background = colours["background"].clone();
+    for (key, value) in failing {
+        if !adjustable_keys.contains(&key) {
+            warn_untouched(&key, value);
+            continue;
+        }
+        let old_hex = colours[&key].clone();
+        let new_hex = adjust_for_contrast(&old_hex, &background, requirement)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to adjust '{}': {}", key, e)))?;
+        let new_value = contrast(&new_hex, &background, requirement.metric).unwrap_or(0.0);
+        println!("Adjusted {} for contrast: #{} ({}) -> #{} ({})", key, old_hex, format_contrast(value, requirement.metric), new_hex, format_contrast(new_value, requirement.metric));
+        if new_value < requirement.min_contrast {
+            eprintln!("Warning: '{}' cannot reach the minimum contrast against this background.", key);
+        }
+        colours.insert(key, new_hex);
+    }
+    Ok(())
+}
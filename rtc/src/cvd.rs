@@ -0,0 +1,81 @@
+use crate::colour_space::{linear_to_srgb, rgb_to_hex, srgb_to_linear};
+use crate::util::hex_to_rgb;
+use clap::ValueEnum;
+
+/// Dichromatic colour vision deficiencies that can be simulated.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CvdType {
+    /// No functioning long-wavelength (red) cones
+    Protanopia,
+    /// No functioning medium-wavelength (green) cones
+    Deuteranopia,
+    /// No functioning short-wavelength (blue) cones
+    Tritanopia,
+}
+
+impl CvdType {
+    pub const ALL: [CvdType; 3] = [CvdType::Protanopia, CvdType::Deuteranopia, CvdType::Tritanopia];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CvdType::Protanopia => "protanopia",
+            CvdType::Deuteranopia => "deuteranopia",
+            CvdType::Tritanopia => "tritanopia",
+        }
+    }
+}
+
+type Matrix = [[f64; 3]; 3];
+
+// Viénot, Brettel & Mollon (1999), expressed directly in linear sRGB.
+const PROTAN_VIENOT: Matrix = [
+    [0.11238, 0.88762, 0.00000],
+    [0.11238, 0.88762, 0.00000],
+    [0.00401, -0.00401, 1.00000],
+];
+const DEUTAN_VIENOT: Matrix = [
+    [0.29275, 0.70725, 0.00000],
+    [0.29275, 0.70725, 0.00000],
+    [-0.02234, 0.02234, 1.00000],
+];
+
+// Brettel, Viénot & Mollon (1997) for tritanopia: one projection per half-plane,
+// selected by the side of the separation plane the colour falls on.
+const TRITAN_BRETTEL_1: Matrix = [
+    [1.01277, 0.13548, -0.14826],
+    [-0.01243, 0.86812, 0.14431],
+    [0.07589, 0.80500, 0.11911],
+];
+const TRITAN_BRETTEL_2: Matrix = [
+    [0.93678, 0.18979, -0.12657],
+    [0.06154, 0.81526, 0.12320],
+    [-0.37562, 1.12767, 0.24796],
+];
+const TRITAN_SEPARATION_NORMAL: [f64; 3] = [0.03901, -0.02788, -0.01113];
+
+fn apply(m: &Matrix, rgb: [f64; 3]) -> [f64; 3] {
+    [
+        m[0][0] * rgb[0] + m[0][1] * rgb[1] + m[0][2] * rgb[2],
+        m[1][0] * rgb[0] + m[1][1] * rgb[1] + m[1][2] * rgb[2],
+        m[2][0] * rgb[0] + m[2][1] * rgb[1] + m[2][2] * rgb[2],
+    ]
+}
+
+/// Returns how `hex` would be perceived with the given deficiency.
+pub fn simulate_hex(hex: &str, cvd: CvdType) -> Result<String, String> {
+    let (r, g, b) = hex_to_rgb(hex)?;
+    let rgb = [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b)];
+
+    let simulated = match cvd {
+        CvdType::Protanopia => apply(&PROTAN_VIENOT, rgb),
+        CvdType::Deuteranopia => apply(&DEUTAN_VIENOT, rgb),
+        CvdType::Tritanopia => {
+            let n = TRITAN_SEPARATION_NORMAL;
+            let side = rgb[0] * n[0] + rgb[1] * n[1] + rgb[2] * n[2];
+            let m = if side >= 0.0 { &TRITAN_BRETTEL_1 } else { &TRITAN_BRETTEL_2 };
+            apply(m, rgb)
+        }
+    };
+
+    Ok(rgb_to_hex(linear_to_srgb(simulated[0]), linear_to_srgb(simulated[1]), linear_to_srgb(simulated[2])))
+}
@@ -0,0 +1,75 @@
+use std::{io, path::PathBuf};
+use crate::backup::read_colours_backup;
+use crate::colour_space::delta_e_hex;
+use crate::colours::{extract_current_colours, ColourMap, COLOUR_KEYS};
+use crate::util::colour_swatch;
+
+struct KeyDiff {
+    key: &'static str,
+    left: Option<String>,
+    right: Option<String>,
+    /// `None` when only one side has a value, which always sorts first.
+    delta_e: Option<f64>,
+}
+
+fn describe(hex: &Option<String>) -> String {
+    match hex {
+        Some(hex) => format!("{} #{}", colour_swatch(hex), hex),
+        None => "     unset  ".to_string(),
+    }
+}
+
+fn diff_colour_maps(left: &ColourMap, right: &ColourMap) -> Vec<KeyDiff> {
+    let mut diffs: Vec<KeyDiff> = COLOUR_KEYS.iter()
+        .filter_map(|&key| {
+            let left = left.get(key).cloned();
+            let right = right.get(key).cloned();
+            let delta_e = match (&left, &right) {
+                (None, None) => return None,
+                (Some(l), Some(r)) => Some(delta_e_hex(l, r).unwrap_or(f64::INFINITY)),
+                _ => None,
+            };
+            Some(KeyDiff { key, left, right, delta_e })
+        })
+        .collect();
+
+    diffs.sort_by(|a, b| {
+        let a = a.delta_e.unwrap_or(f64::INFINITY);
+        let b = b.delta_e.unwrap_or(f64::INFINITY);
+        b.total_cmp(&a)
+    });
+    diffs
+}
+
+/// Compares backup `backup_a` with `backup_b`, or with the live kitty.conf colours
+/// when no second backup is given. Keys are listed by CIEDE2000 ΔE, largest first.
+pub fn diff_themes(backup_a: &str, backup_b: Option<&str>, config_file_path: Option<&PathBuf>) -> Result<(), io::Error> {
+    let left = read_colours_backup(&Some(backup_a.to_string()))?.colours;
+    let (right, right_label) = match (backup_b, config_file_path) {
+        (Some(name), _) => (read_colours_backup(&Some(name.to_string()))?.colours, format!("'{}'", name)),
+        (None, Some(path)) => (extract_current_colours(path)?, path.display().to_string()),
+        (None, None) => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "No second theme to compare against"));
+        }
+    };
+
+    let diffs = diff_colour_maps(&left, &right);
+
+    println!("\nComparing '{}' → {}:", backup_a, right_label);
+    println!("  {:<11} {:<12}    {:<12}   ΔE2000", "key", "before", "after");
+    let mut changed = 0;
+    for diff in &diffs {
+        let delta = match diff.delta_e {
+            Some(d) if d < 0.005 => "      -".to_string(),
+            Some(d) => format!("{:7.2}", d),
+            None => "      *".to_string(),
+        };
+        if diff.delta_e.is_none_or(|d| d >= 0.005) {
+            changed += 1;
+        }
+        println!("  {:<11} {}  → {}  {}", diff.key, describe(&diff.left), describe(&diff.right), delta);
+    }
+
+    println!("\n{} of {} keys differ (* = set on one side only).", changed, diffs.len());
+    Ok(())
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use crate::colour_space::{Oklab, Oklch};
+use crate::colours::{is_key_selected, ColourMap, COLOUR_KEYS};
+use crate::util::generate_random_colour_hex;
+use clap::ValueEnum;
+use rand::Rng;
+
+/// How `-r` picks colours.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteStrategy {
+    /// Independent random 24-bit colour for every key (the original behaviour)
+    Random,
+    /// Base hue plus its opposite
+    Complementary,
+    /// Base hue and its neighbours 30° either side
+    Analogous,
+    /// Three hues 120° apart
+    Triadic,
+    /// Four hues 90° apart
+    Tetradic,
+    /// Shades of a single hue
+    Monochrome,
+    /// Six hues spread evenly around the wheel, starting at the base hue
+    BaseHue,
+    /// Each ANSI colour stays near its conventional hue (color1 red, color2 green, ...)
+    Semantic,
+}
+
+/// Canonical OKLCH hues of the six chromatic ANSI colours, color1 to color6
+/// (red, green, yellow, blue, magenta, cyan).
+pub const ANSI_HUES: [f64; 6] = [29.0, 142.0, 110.0, 264.0, 328.0, 195.0];
+
+/// The strategy for `-r`, plus the tunables of the `Semantic` strategy. The
+/// harmony strategies use fixed lightness and chroma ranges per role and ignore
+/// `hue_jitter`, `chroma` and `lightness`.
+#[derive(Debug, Clone, Copy)]
+pub struct PaletteOptions {
+    pub strategy: PaletteStrategy,
+    /// Anchor hue in degrees; random when `None`. For `Semantic` it only tints the
+    /// neutrals, which stay untinted grey when `None`.
+    pub base_hue: Option<f64>,
+    /// `Semantic` only: maximum deviation in degrees from each ANSI colour's target hue.
+    pub hue_jitter: f64,
+    /// `Semantic` only: OKLCH chroma range for color1-6.
+    pub chroma: (f64, f64),
+    /// `Semantic` only: OKLCH lightness range for color1-6; brights sit slightly above it.
+    pub lightness: (f64, f64),
+}
+
+impl Default for PaletteOptions {
+    fn default() -> Self {
+        PaletteOptions {
+            strategy: PaletteStrategy::Random,
+            base_hue: None,
+            hue_jitter: 12.0,
+            chroma: (0.10, 0.16),
+            lightness: (0.62, 0.72),
+        }
+    }
+}
+
+impl PaletteStrategy {
+    fn hue_offsets(self) -> &'static [f64] {
+        match self {
+            PaletteStrategy::Random | PaletteStrategy::Semantic => &[],
+            PaletteStrategy::Complementary => &[0.0, 180.0],
+            PaletteStrategy::Analogous => &[-30.0, 0.0, 30.0],
+            PaletteStrategy::Triadic => &[0.0, 120.0, 240.0],
+            PaletteStrategy::Tetradic => &[0.0, 90.0, 180.0, 270.0],
+            PaletteStrategy::Monochrome => &[0.0],
+            PaletteStrategy::BaseHue => &[0.0, 60.0, 120.0, 180.0, 240.0, 300.0],
+        }
+    }
+}
+
+fn pick<R: Rng + ?Sized>(rng: &mut R, range: (f64, f64)) -> f64 {
+    rng.random_range(range.0..=range.1)
+}
+
+/// Generates a full 19-key scheme for `options.strategy`.
+///
+/// Hues come from the strategy's anchors around the base hue, or from `ANSI_HUES`
+/// for `Semantic`; lightness and chroma come from each key's role in a dark scheme:
+/// a dark, barely tinted background, a light foreground, mid-lightness ANSI colours
+/// and slightly lighter brights. Only `Semantic` takes color1-6's jitter, chroma
+/// and lightness from `options`.
+pub fn generate_palette<R: Rng + ?Sized>(options: &PaletteOptions, rng: &mut R) -> ColourMap {
+    let mut palette: ColourMap = HashMap::new();
+    let strategy = options.strategy;
+
+    if strategy == PaletteStrategy::Random {
+        for &key in COLOUR_KEYS.iter() {
+            palette.insert(key.to_string(), generate_random_colour_hex(rng));
+        }
+        return palette;
+    }
+
+    let semantic = strategy == PaletteStrategy::Semantic;
+    let (base_hue, neutral_chroma) = match options.base_hue {
+        Some(hue) => (hue, (0.01, 0.035)),
+        None if semantic => (0.0, (0.0, 0.0)),
+        None => (rng.random_range(0.0..360.0), (0.01, 0.035)),
+    };
+    let anchors: Vec<f64> = match strategy {
+        PaletteStrategy::Semantic => ANSI_HUES.to_vec(),
+        _ => strategy.hue_offsets().iter().map(|offset| base_hue + offset).collect(),
+    };
+
+    let neutral = |rng: &mut R, l: (f64, f64)| Oklch::new(pick(rng, l), pick(rng, neutral_chroma), base_hue).to_hex();
+    palette.insert("background".to_string(), neutral(rng, (0.17, 0.24)));
+    palette.insert("foreground".to_string(), neutral(rng, (0.86, 0.93)));
+    palette.insert("color0".to_string(), neutral(rng, (0.26, 0.32)));
+    palette.insert("color7".to_string(), neutral(rng, (0.74, 0.80)));
+    palette.insert("color8".to_string(), neutral(rng, (0.42, 0.50)));
+    palette.insert("color15".to_string(), neutral(rng, (0.93, 0.97)));
+
+    let cursor_hue = anchors[rng.random_range(0..anchors.len())];
+    palette.insert("cursor".to_string(), Oklch::new(pick(rng, (0.78, 0.86)), pick(rng, (0.10, 0.15)), cursor_hue).to_hex());
+
+    for i in 1..=6 {
+        let anchor = anchors[(i - 1) % anchors.len()];
+        let (l, c, h) = match strategy {
+            // A single hue only stays readable if the six slots differ in lightness.
+            PaletteStrategy::Monochrome => (0.50 + 0.05 * i as f64, pick(rng, (0.06, 0.16)), base_hue + pick(rng, (-6.0, 6.0))),
+            PaletteStrategy::Semantic => {
+                let jitter = options.hue_jitter;
+                (pick(rng, options.lightness), pick(rng, options.chroma), anchor + pick(rng, (-jitter, jitter)))
+            }
+            _ => (pick(rng, (0.62, 0.72)), pick(rng, (0.10, 0.16)), anchor + pick(rng, (-12.0, 12.0))),
+        };
+        palette.insert(format!("color{}", i), Oklch::new(l, c, h).to_hex());
+        palette.insert(format!("color{}", i + 8), Oklch::new(l + pick(rng, (0.07, 0.11)), c * 1.05, h).to_hex());
+    }
+
+    palette
+}
+
+pub const DEFAULT_BRIGHT_AMOUNT: f64 = 0.25;
+
+/// Derives a bright variant of `hex` in OKLCH: lightness moves `amount` of the way
+/// towards white and chroma is boosted slightly, keeping the hue.
+pub fn derive_bright_hex(hex: &str, amount: f64) -> Result<String, String> {
+    let lch: Oklch = Oklab::from_hex(hex)?.into();
+    let l = lch.l + (1.0 - lch.l) * amount;
+    let c = lch.c * (1.0 + 0.5 * amount);
+    Ok(Oklch::new(l, c, lch.h).to_hex())
+}
+
+/// Computes color8-15 from color0-7 in `colours`.
+///
+/// A bright key is derived when it is selected by the `-f`/`-e` lists, or when `-f`
+/// names its normal counterpart (so `-f c1` also refreshes color9).
+pub fn derive_brights(colours: &ColourMap, amount: f64, forced_keys: &[String], excluded_keys: &[String]) -> ColourMap {
+    let mut derived: ColourMap = HashMap::new();
+    for i in 0..8 {
+        let normal_key = format!("color{}", i);
+        let bright_key = format!("color{}", i + 8);
+
+        let selected = is_key_selected(&bright_key, forced_keys, excluded_keys)
+            || forced_keys.contains(&normal_key);
+        if !selected {
+            continue;
+        }
+
+        match colours.get(&normal_key).map(|hex| derive_bright_hex(hex, amount)) {
+            Some(Ok(hex)) => {
+                derived.insert(bright_key, hex);
+            }
+            Some(Err(e)) => eprintln!("Warning: Cannot derive '{}' from '{}': {}", bright_key, normal_key, e),
+            None => eprintln!("Warning: '{}' has no value, so '{}' cannot be derived.", normal_key, bright_key),
+        }
+    }
+    derived
+}
+
+/// Whether a generated scheme uses a dark or a light background.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemeVariant {
+    Dark,
+    Light,
+}
+
+/// Builds a complete 19-key scheme around one accent colour.
+///
+/// The background, foreground and neutrals are tinted with the seed's hue, the six
+/// chromatic ANSI colours keep their conventional hues (nudged towards the seed)
+/// but take on the seed's chroma and lightness, and the cursor is the seed itself.
+pub fn generate_seed_palette(seed_hex: &str, variant: SchemeVariant) -> Result<ColourMap, String> {
+    let seed: Oklch = Oklab::from_hex(seed_hex)?.into();
+    let tint = (seed.c * 0.2).min(0.03);
+    let chroma = seed.c.clamp(0.06, 0.18);
+
+    // Lightness for bg, fg, color0, color7, color8, color15, then the ANSI band and
+    // the direction brights move in.
+    let (neutrals, ansi_l, bright_step) = match variant {
+        SchemeVariant::Dark => ([0.20, 0.90, 0.28, 0.76, 0.46, 0.95], seed.l.clamp(0.62, 0.74), 0.08),
+        SchemeVariant::Light => ([0.97, 0.28, 0.30, 0.84, 0.50, 0.92], seed.l.clamp(0.46, 0.58), -0.07),
+    };
+
+    let mut palette: ColourMap = HashMap::new();
+    for (key, l) in ["background", "foreground", "color0", "color7", "color8", "color15"].iter().zip(neutrals) {
+        palette.insert(key.to_string(), Oklch::new(l, tint, seed.h).to_hex());
+    }
+    palette.insert("cursor".to_string(), seed_hex.to_lowercase());
+
+    for (i, &canonical_hue) in ANSI_HUES.iter().enumerate() {
+        let mut towards_seed = seed.h - canonical_hue;
+        if towards_seed > 180.0 {
+            towards_seed -= 360.0;
+        } else if towards_seed < -180.0 {
+            towards_seed += 360.0;
+        }
+        let hue = canonical_hue + towards_seed.clamp(-20.0, 20.0) * 0.5;
+        palette.insert(format!("color{}", i + 1), Oklch::new(ansi_l, chroma, hue).to_hex());
+        palette.insert(format!("color{}", i + 9), Oklch::new(ansi_l + bright_step, chroma * 1.05, hue).to_hex());
+    }
+
+    Ok(palette)
+}
+
+/// Normal/bright pairs whose roles trade places when a scheme is inverted, so that
+/// color0 stays the "black" slot and color7 the "white" one.
+const INVERTED_ROLE_SWAPS: [(&str, &str); 2] = [("color0", "color7"), ("color8", "color15")];
+
+/// Mirrors OKLCH lightness within the scheme's own range, keeping hue and chroma,
+/// then swaps color0 with color7 and color8 with color15.
+pub fn invert_scheme(colours: &ColourMap) -> Result<ColourMap, String> {
+    let mut lch: Vec<(String, Oklch)> = Vec::new();
+    for (key, hex) in colours {
+        lch.push((key.clone(), Oklab::from_hex(hex)?.into()));
+    }
+    let l_min = lch.iter().map(|(_, c)| c.l).fold(f64::INFINITY, f64::min);
+    let l_max = lch.iter().map(|(_, c)| c.l).fold(f64::NEG_INFINITY, f64::max);
+
+    let mut inverted: ColourMap = HashMap::new();
+    for (key, c) in lch {
+        inverted.insert(key, Oklch::new(l_min + l_max - c.l, c.c, c.h).to_hex());
+    }
+    for (a, b) in INVERTED_ROLE_SWAPS {
+        let hex_a = inverted.remove(a);
+        let hex_b = inverted.remove(b);
+        if let Some(hex) = hex_a {
+            inverted.insert(b.to_string(), hex);
+        }
+        if let Some(hex) = hex_b {
+            inverted.insert(a.to_string(), hex);
+        }
+    }
+    Ok(inverted)
+}
@@ -0,0 +1,191 @@
+use std::{collections::HashMap, io, path::Path};
+use crate::colour_space::{srgb_to_linear, Oklab, Oklch};
+use crate::colours::ColourMap;
+use crate::contrast::{adjust_for_contrast, ContrastFix, ContrastMetric, ContrastRequirement};
+use crate::generate::{SchemeVariant, ANSI_HUES};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Images are downscaled to fit in this many pixels per side before clustering.
+const SAMPLE_SIZE: u32 = 128;
+const KMEANS_ITERATIONS: usize = 24;
+/// Clusters below this chroma are treated as greys and never drive an ANSI hue.
+const MIN_ACCENT_CHROMA: f64 = 0.03;
+/// How far (in degrees) an ANSI colour may drift from its canonical hue towards
+/// the nearest image colour, so that color1 still reads as red and so on.
+const MAX_HUE_DRIFT: f64 = 25.0;
+const ANSI_MIN_CONTRAST: f64 = 4.5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Cluster {
+    pub centre: Oklab,
+    /// Share of sampled pixels in this cluster, 0 to 1.
+    pub weight: f64,
+}
+
+fn load_oklab_pixels(image_path: &Path) -> Result<Vec<Oklab>, io::Error> {
+    let reader = image::ImageReader::open(image_path).map_err(|e| {
+        eprintln!("Error: Failed to read image {}: {}", image_path.display(), e);
+        io::Error::new(e.kind(), format!("Failed to read image {}: {}", image_path.display(), e))
+    })?;
+    // Sniff the format from the file's contents, so wallpapers without a
+    // .png/.jpg extension still decode.
+    let image = reader.with_guessed_format()?.decode().map_err(|e| {
+        eprintln!("Error: Failed to decode image {}: {}", image_path.display(), e);
+        io::Error::new(io::ErrorKind::InvalidData, format!("Failed to decode image {}: {}", image_path.display(), e))
+    })?;
+    let sample = image.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE).to_rgba8();
+
+    Ok(sample.pixels()
+        .filter(|p| p[3] >= 128)
+        .map(|p| Oklab::from_linear_rgb(srgb_to_linear(p[0]), srgb_to_linear(p[1]), srgb_to_linear(p[2])))
+        .collect())
+}
+
+fn distance_sq(a: Oklab, b: Oklab) -> f64 {
+    (a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)
+}
+
+/// k-means in OKLab with k-means++ seeding. A fixed RNG seed keeps the result
+/// stable for the same image.
+pub fn cluster_pixels(pixels: &[Oklab], k: usize) -> Vec<Cluster> {
+    if pixels.is_empty() || k == 0 {
+        return Vec::new();
+    }
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut centres = vec![pixels[rng.random_range(0..pixels.len())]];
+    while centres.len() < k.min(pixels.len()) {
+        let distances: Vec<f64> = pixels.iter()
+            .map(|&p| centres.iter().map(|&c| distance_sq(p, c)).fold(f64::INFINITY, f64::min))
+            .collect();
+        let total: f64 = distances.iter().sum();
+        if total <= f64::EPSILON {
+            break;
+        }
+        let mut target = rng.random_range(0.0..total);
+        let idx = distances.iter().position(|&d| {
+            target -= d;
+            target <= 0.0
+        }).unwrap_or(pixels.len() - 1);
+        centres.push(pixels[idx]);
+    }
+
+    let mut assignments = vec![0usize; pixels.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        for (i, &p) in pixels.iter().enumerate() {
+            assignments[i] = (0..centres.len())
+                .min_by(|&a, &b| distance_sq(p, centres[a]).total_cmp(&distance_sq(p, centres[b])))
+                .unwrap_or(0);
+        }
+        let mut sums = vec![(0.0, 0.0, 0.0, 0usize); centres.len()];
+        for (i, &p) in pixels.iter().enumerate() {
+            let s = &mut sums[assignments[i]];
+            s.0 += p.l;
+            s.1 += p.a;
+            s.2 += p.b;
+            s.3 += 1;
+        }
+        for (centre, s) in centres.iter_mut().zip(&sums) {
+            if s.3 > 0 {
+                let n = s.3 as f64;
+                *centre = Oklab { l: s.0 / n, a: s.1 / n, b: s.2 / n };
+            }
+        }
+    }
+
+    let mut counts = vec![0usize; centres.len()];
+    for &a in &assignments {
+        counts[a] += 1;
+    }
+    let mut clusters: Vec<Cluster> = centres.into_iter()
+        .zip(counts)
+        .filter(|&(_, count)| count > 0)
+        .map(|(centre, count)| Cluster { centre, weight: count as f64 / pixels.len() as f64 })
+        .collect();
+    clusters.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    clusters
+}
+
+fn hue_distance(a: f64, b: f64) -> f64 {
+    let d = (a - b).rem_euclid(360.0);
+    if d > 180.0 { d - 360.0 } else { d }
+}
+
+/// Maps image clusters onto the 19 kitty keys.
+///
+/// The background comes from the darkest (or, for light schemes, lightest)
+/// prominent cluster and the neutrals share its hue. Each chromatic ANSI colour
+/// takes the hue of the most colourful nearby cluster but stays within
+/// `MAX_HUE_DRIFT` of its canonical hue, is placed in a readable lightness band and
+/// is then pushed to at least `ANSI_MIN_CONTRAST` against the background.
+pub fn palette_from_clusters(clusters: &[Cluster], variant: SchemeVariant) -> Result<ColourMap, String> {
+    if clusters.is_empty() {
+        return Err("The image has no opaque pixels".to_string());
+    }
+
+    let prominent: Vec<&Cluster> = clusters.iter().filter(|c| c.weight >= 0.03).collect();
+    let candidates = if prominent.is_empty() { clusters.iter().collect() } else { prominent };
+    let base = match variant {
+        SchemeVariant::Dark => candidates.iter().min_by(|a, b| a.centre.l.total_cmp(&b.centre.l)),
+        SchemeVariant::Light => candidates.iter().max_by(|a, b| a.centre.l.total_cmp(&b.centre.l)),
+    }.map(|c| Oklch::from(c.centre)).ok_or("No background candidate")?;
+
+    let tint = base.c.min(0.035);
+    let (neutrals, ansi_band, bright_step) = match variant {
+        SchemeVariant::Dark => ([base.l.clamp(0.14, 0.24), 0.90, 0.30, 0.77, 0.47, 0.95], (0.62, 0.75), 0.08),
+        SchemeVariant::Light => ([base.l.clamp(0.94, 0.98), 0.28, 0.30, 0.84, 0.50, 0.92], (0.45, 0.58), -0.07),
+    };
+
+    let mut palette: ColourMap = HashMap::new();
+    for (key, l) in ["background", "foreground", "color0", "color7", "color8", "color15"].iter().zip(neutrals) {
+        palette.insert(key.to_string(), Oklch::new(l, tint, base.h).to_hex());
+    }
+    let background = palette["background"].clone();
+    let requirement = ContrastRequirement {
+        min_contrast: ANSI_MIN_CONTRAST,
+        metric: ContrastMetric::Wcag,
+        fix: ContrastFix::Adjust,
+    };
+
+    let accents: Vec<Oklch> = clusters.iter()
+        .map(|c| Oklch::from(c.centre))
+        .filter(|c| c.c >= MIN_ACCENT_CHROMA)
+        .collect();
+
+    for (i, &canonical_hue) in ANSI_HUES.iter().enumerate() {
+        // Prefer colourful clusters close to the canonical hue.
+        let nearest = accents.iter()
+            .min_by(|a, b| {
+                let score = |c: &&Oklch| hue_distance(c.h, canonical_hue).abs() - c.c * 100.0;
+                score(a).total_cmp(&score(b))
+            });
+        let (hue, chroma, lightness) = match nearest {
+            Some(c) => (
+                canonical_hue + hue_distance(c.h, canonical_hue).clamp(-MAX_HUE_DRIFT, MAX_HUE_DRIFT),
+                c.c.clamp(0.08, 0.19),
+                c.l.clamp(ansi_band.0, ansi_band.1),
+            ),
+            None => (canonical_hue, 0.10, (ansi_band.0 + ansi_band.1) / 2.0),
+        };
+
+        let normal = adjust_for_contrast(&Oklch::new(lightness, chroma, hue).to_hex(), &background, &requirement)?;
+        let bright = adjust_for_contrast(&Oklch::new(lightness + bright_step, chroma * 1.05, hue).to_hex(), &background, &requirement)?;
+        palette.insert(format!("color{}", i + 1), normal);
+        palette.insert(format!("color{}", i + 9), bright);
+    }
+
+    let cursor = accents.iter()
+        .max_by(|a, b| (a.c * a.l).total_cmp(&(b.c * b.l)))
+        .map(|c| Oklch::new(c.l.clamp(ansi_band.0, ansi_band.1), c.c, c.h))
+        .unwrap_or(Oklch::new(neutrals[1], tint, base.h));
+    palette.insert("cursor".to_string(), adjust_for_contrast(&cursor.to_hex(), &background, &requirement)?);
+
+    Ok(palette)
+}
+
+pub fn palette_from_image(image_path: &Path, variant: SchemeVariant, clusters: usize) -> Result<ColourMap, io::Error> {
+    let pixels = load_oklab_pixels(image_path)?;
+    let clusters = cluster_pixels(&pixels, clusters);
+    palette_from_clusters(&clusters, variant).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
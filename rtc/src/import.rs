@@ -0,0 +1,78 @@
+use std::{fs, io, path::Path};
+use crate::backup::{write_colours_backup, ColourBackup};
+use crate::colours::{
+    extract_colours_from_content,
+    load_colours_from_backup,
+    ColourMap,
+    COLOUR_KEYS,
+    EXTENDED_COLOUR_KEYS,
+};
+use crate::config::require_kitty_config_path;
+
+/// Reads a `## key: value` header line as used by the kitty-themes project.
+fn theme_metadata(content: &str, field: &str) -> Option<String> {
+    content.lines()
+        .filter_map(|line| line.trim().strip_prefix("##"))
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(field))
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn lowercase_values(colours: ColourMap) -> ColourMap {
+    colours.into_iter().map(|(key, hex)| (key, hex.to_lowercase())).collect()
+}
+
+/// Imports a kitty theme `.conf` file (a kitty.conf subset, as shipped by
+/// kitty-themes) as an rtc backup, optionally applying it right away.
+///
+/// The backup name defaults to the file name without its extension. The theme's
+/// `## name:` and `## blurb:` headers become the backup's description.
+pub fn import_kitty_theme(file_path: &Path, backup_name: Option<String>, apply: bool) -> Result<(), io::Error> {
+    let content = fs::read_to_string(file_path).map_err(|e| {
+        eprintln!("Error: Failed to read theme file {}: {}", file_path.display(), e);
+        io::Error::new(e.kind(), format!("Failed to read theme file: {}", e))
+    })?;
+
+    let colours = lowercase_values(extract_colours_from_content(&content, &COLOUR_KEYS));
+    let extended = lowercase_values(extract_colours_from_content(&content, &EXTENDED_COLOUR_KEYS));
+    if colours.is_empty() {
+        eprintln!("Error: No colour settings found in {}. Is it a kitty theme file?", file_path.display());
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "No colours in theme file"));
+    }
+
+    let name = backup_name.unwrap_or_else(|| {
+        file_path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| "imported".to_string())
+    });
+    let description = match (theme_metadata(&content, "name"), theme_metadata(&content, "blurb")) {
+        (Some(title), Some(blurb)) => format!("{}: {}", title, blurb),
+        (Some(title), None) => title,
+        (None, Some(blurb)) => blurb,
+        (None, None) => format!("Imported from {}", file_path.display()),
+    };
+
+    let mut backup = ColourBackup::new(colours, Some(file_path), Some(description), vec!["imported".to_string()]);
+    backup.extended = extended;
+
+    let unset_keys = backup.unset_keys();
+    println!(
+        "Found {} of {} colours and {} extended colour(s) in {}.",
+        backup.colours.len(),
+        COLOUR_KEYS.len(),
+        backup.extended.len(),
+        file_path.display()
+    );
+    if !unset_keys.is_empty() {
+        println!("Not set by this theme (recorded as unset): {}", unset_keys.join(", "));
+    }
+
+    write_colours_backup(&Some(name.clone()), &backup)?;
+    println!("Theme imported as '{}'.", name);
+
+    if apply {
+        load_colours_from_backup(&require_kitty_config_path()?, Some(name), &None, &None)
+    } else {
+        println!("Load it with `rtc -l -n {}`.", name);
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use std::{fs, io, path::Path};
+use crate::backup::{read_colours_backup, write_colours_backup, ColourBackup};
+use crate::colour_space::{linear_to_srgb, rgb_to_hex};
+use crate::colours::{extract_current_colours, extract_extended_colours, load_colours_from_backup, ColourMap, COLOUR_KEYS};
+use crate::config::require_kitty_config_path;
+use crate::util::hex_to_rgb;
+use plist::{Dictionary, Value};
+
+/// iTerm2 colour entries and the kitty keys they correspond to. The first 19 cover
+/// `COLOUR_KEYS`; the rest are the extended keys iTerm2 also has a setting for.
+const ITERM_KEYS: [(&str, &str); 23] = [
+    ("Foreground Color", "foreground"),
+    ("Background Color", "background"),
+    ("Cursor Color", "cursor"),
+    ("Ansi 0 Color", "color0"),
+    ("Ansi 1 Color", "color1"),
+    ("Ansi 2 Color", "color2"),
+    ("Ansi 3 Color", "color3"),
+    ("Ansi 4 Color", "color4"),
+    ("Ansi 5 Color", "color5"),
+    ("Ansi 6 Color", "color6"),
+    ("Ansi 7 Color", "color7"),
+    ("Ansi 8 Color", "color8"),
+    ("Ansi 9 Color", "color9"),
+    ("Ansi 10 Color", "color10"),
+    ("Ansi 11 Color", "color11"),
+    ("Ansi 12 Color", "color12"),
+    ("Ansi 13 Color", "color13"),
+    ("Ansi 14 Color", "color14"),
+    ("Ansi 15 Color", "color15"),
+    ("Cursor Text Color", "cursor_text_color"),
+    ("Selected Text Color", "selection_foreground"),
+    ("Selection Color", "selection_background"),
+    ("Link Color", "url_color"),
+];
+
+/// Linear-light Apple Generic RGB ("Calibrated", gamma 1.8) to linear sRGB.
+const GENERIC_RGB_TO_SRGB: [[f64; 3]; 3] = [
+    [1.02525, -0.02655, 0.00130],
+    [0.01939, 0.94803, 0.03258],
+    [-0.00177, -0.00144, 1.00321],
+];
+
+/// Linear Display P3 to linear sRGB; both use a D65 white point.
+const DISPLAY_P3_TO_SRGB: [[f64; 3]; 3] = [
+    [1.22494, -0.22494, 0.0],
+    [-0.04206, 1.04206, 0.0],
+    [-0.01964, -0.07864, 1.09827],
+];
+
+fn srgb_component_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn convert_to_srgb(matrix: &[[f64; 3]; 3], linear: [f64; 3]) -> String {
+    let [r, g, b] = matrix.map(|row| row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2]);
+    rgb_to_hex(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+}
+
+/// Converts an iTerm2 colour dictionary to a hex code. Colours in the older
+/// "Calibrated" space (also assumed when "Color Space" is missing) and in "P3" are
+/// converted to sRGB, clipping anything outside its gamut.
+fn iterm_colour_to_hex(entry: &Dictionary) -> Result<String, String> {
+    let component = |name: &str| -> Result<f64, String> {
+        let value = entry.get(name).ok_or_else(|| format!("missing '{}'", name))?;
+        value.as_real()
+            .or_else(|| value.as_signed_integer().map(|i| i as f64))
+            .map(|c| c.clamp(0.0, 1.0))
+            .ok_or_else(|| format!("'{}' is not a number", name))
+    };
+    let rgb = [component("Red Component")?, component("Green Component")?, component("Blue Component")?];
+
+    let space = entry.get("Color Space").and_then(Value::as_string).unwrap_or("Calibrated");
+    match space {
+        "sRGB" => {
+            let [r, g, b] = rgb.map(|c| (c * 255.0).round() as u8);
+            Ok(rgb_to_hex(r, g, b))
+        }
+        "Calibrated" => Ok(convert_to_srgb(&GENERIC_RGB_TO_SRGB, rgb.map(|c| c.powf(1.8)))),
+        "P3" => Ok(convert_to_srgb(&DISPLAY_P3_TO_SRGB, rgb.map(srgb_component_to_linear))),
+        other => Err(format!("unsupported colour space '{}'", other)),
+    }
+}
+
+/// Imports an iTerm2 `.itermcolors` file as a backup, optionally applying it.
+/// Colours iTerm2 has beyond `COLOUR_KEYS` (selection, cursor text, links) are
+/// kept as extended colours.
+pub fn import_itermcolors(file_path: &Path, backup_name: Option<String>, apply: bool) -> Result<(), io::Error> {
+    let content = fs::read(file_path).map_err(|e| {
+        eprintln!("Error: Failed to read preset file {}: {}", file_path.display(), e);
+        io::Error::new(e.kind(), format!("Failed to read preset file: {}", e))
+    })?;
+    let value = Value::from_reader(io::Cursor::new(content)).map_err(|e| {
+        eprintln!("Error: {} is not a valid property list: {}", file_path.display(), e);
+        io::Error::new(io::ErrorKind::InvalidData, "Invalid .itermcolors file")
+    })?;
+    let Some(root) = value.as_dictionary() else {
+        eprintln!("Error: {} is not an iTerm2 colour preset (expected a top-level dictionary).", file_path.display());
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid .itermcolors file"));
+    };
+
+    let mut colours = ColourMap::new();
+    let mut extended = ColourMap::new();
+    for (iterm_key, key) in ITERM_KEYS {
+        let Some(entry) = root.get(iterm_key).and_then(Value::as_dictionary) else {
+            continue;
+        };
+        let hex = iterm_colour_to_hex(entry).map_err(|e| {
+            eprintln!("Error: Invalid '{}' in {}: {}.", iterm_key, file_path.display(), e);
+            io::Error::new(io::ErrorKind::InvalidData, "Invalid .itermcolors colour")
+        })?;
+        if COLOUR_KEYS.contains(&key) {
+            colours.insert(key.to_string(), hex);
+        } else {
+            extended.insert(key.to_string(), hex);
+        }
+    }
+    if colours.is_empty() {
+        eprintln!("Error: No colours found in {}. Is it an iTerm2 .itermcolors file?", file_path.display());
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "No colours in .itermcolors file"));
+    }
+
+    let name = backup_name.unwrap_or_else(|| {
+        file_path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| "imported".to_string())
+    });
+    let description = format!("Imported from {}", file_path.display());
+    let mut backup = ColourBackup::new(colours, Some(file_path), Some(description), vec!["imported".to_string(), "iterm2".to_string()]);
+    backup.extended = extended;
+
+    let unset_keys = backup.unset_keys();
+    println!(
+        "Found {} of {} colours and {} extended colour(s) in {}.",
+        backup.colours.len(),
+        COLOUR_KEYS.len(),
+        backup.extended.len(),
+        file_path.display()
+    );
+    if !unset_keys.is_empty() {
+        println!("Not set by this preset (recorded as unset): {}", unset_keys.join(", "));
+    }
+
+    write_colours_backup(&Some(name.clone()), &backup)?;
+    println!("iTerm2 preset imported as '{}'.", name);
+
+    if apply {
+        load_colours_from_backup(&require_kitty_config_path()?, Some(name), &None, &None)
+    } else {
+        println!("Load it with `rtc -l -n {}`.", name);
+        Ok(())
+    }
+}
+
+fn hex_to_iterm_colour(hex: &str) -> Result<Value, String> {
+    let (r, g, b) = hex_to_rgb(hex)?;
+    let mut entry = Dictionary::new();
+    entry.insert("Alpha Component".to_string(), Value::Real(1.0));
+    entry.insert("Blue Component".to_string(), Value::Real(b as f64 / 255.0));
+    entry.insert("Color Space".to_string(), Value::String("sRGB".to_string()));
+    entry.insert("Green Component".to_string(), Value::Real(g as f64 / 255.0));
+    entry.insert("Red Component".to_string(), Value::Real(r as f64 / 255.0));
+    Ok(Value::Dictionary(entry))
+}
+
+/// Exports a backup, or the live kitty.conf when `backup_name` is `None`, as an
+/// iTerm2 `.itermcolors` preset in the sRGB colour space. Writes to `output` or
+/// prints the XML to stdout.
+pub fn export_itermcolors(backup_name: Option<String>, output: Option<&Path>) -> Result<(), io::Error> {
+    let (colours, extended) = match &backup_name {
+        Some(_) => {
+            let backup = read_colours_backup(&backup_name)?;
+            (backup.colours, backup.extended)
+        }
+        None => {
+            let config_file_path = require_kitty_config_path()?;
+            (extract_current_colours(&config_file_path)?, extract_extended_colours(&config_file_path)?)
+        }
+    };
+
+    let mut root = Dictionary::new();
+    for (iterm_key, key) in ITERM_KEYS {
+        let Some(hex) = colours.get(key).or_else(|| extended.get(key)) else {
+            continue;
+        };
+        let entry = hex_to_iterm_colour(hex).map_err(|e| {
+            eprintln!("Error: Cannot export {} ('{}'): {}.", key, hex, e);
+            io::Error::new(io::ErrorKind::InvalidData, "Cannot export colour")
+        })?;
+        root.insert(iterm_key.to_string(), entry);
+    }
+    if root.is_empty() {
+        eprintln!("Error: There are no colours to export.");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "No colours to export"));
+    }
+
+    let mut xml = Vec::new();
+    Value::Dictionary(root).to_writer_xml(&mut xml)
+        .map_err(|e| io::Error::other(format!("Failed to serialise .itermcolors: {}", e)))?;
+    xml.push(b'\n');
+
+    match output {
+        Some(path) => {
+            fs::write(path, xml)
+                .map_err(|e| io::Error::new(e.kind(), format!("Failed to write {}: {}", path.display(), e)))?;
+            println!("Exported iTerm2 preset to {}.", path.display());
+        }
+        None => print!("{}", String::from_utf8_lossy(&xml)),
+    }
+    Ok(())
+}
@@ -0,0 +1,262 @@
+use std::io;
+use crate::backup::read_colours_backup;
+use crate::colour_space::{delta_e_hex, Oklab};
+use crate::colours::{extract_current_colours, ColourMap, COLOUR_KEYS};
+use crate::config::require_kitty_config_path;
+use crate::contrast::wcag_contrast_ratio;
+use crate::cvd::{simulate_hex, CvdType};
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Below this WCAG ratio the cursor is considered invisible on the background.
+pub const MIN_CURSOR_CONTRAST: f64 = 1.5;
+/// Below this ΔE2000 a colour is considered identical to the background.
+pub const IDENTICAL_DELTA_E: f64 = 1.0;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LintIssue {
+    pub check: &'static str,
+    pub severity: Severity,
+    pub keys: Vec<String>,
+    pub value: f64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LintOptions {
+    /// Minimum WCAG ratio between foreground and background.
+    pub min_contrast: f64,
+    /// Minimum ΔE2000 between any two normal (or any two bright) ANSI colours.
+    pub min_delta_e: f64,
+    /// Minimum ΔE2000 between those pairs under simulated colour blindness; 0
+    /// skips the check.
+    pub min_cvd_delta_e: f64,
+}
+
+#[derive(Serialize)]
+struct LintReport<'a> {
+    source: &'a str,
+    errors: usize,
+    warnings: usize,
+    issues: &'a [LintIssue],
+}
+
+fn issue(check: &'static str, severity: Severity, keys: &[&str], value: f64, message: String) -> LintIssue {
+    LintIssue { check, severity, keys: keys.iter().map(|k| k.to_string()).collect(), value, message }
+}
+
+fn check_fg_contrast(colours: &ColourMap, options: &LintOptions, issues: &mut Vec<LintIssue>) {
+    let (Some(fg), Some(bg)) = (colours.get("foreground"), colours.get("background")) else {
+        return;
+    };
+    if let Ok(ratio) = wcag_contrast_ratio(fg, bg)
+        && ratio < options.min_contrast
+    {
+        issues.push(issue(
+            "low-contrast",
+            Severity::Error,
+            &["foreground", "background"],
+            ratio,
+            format!("foreground/background contrast is {:.2}:1, below {:.2}:1", ratio, options.min_contrast),
+        ));
+    }
+}
+
+fn check_close_ansi_colours(colours: &ColourMap, options: &LintOptions, issues: &mut Vec<LintIssue>) {
+    for group in [0..8, 8..16] {
+        let keys: Vec<String> = group.map(|i| format!("color{}", i)).collect();
+        for (i, a) in keys.iter().enumerate() {
+            for b in &keys[i + 1..] {
+                let (Some(hex_a), Some(hex_b)) = (colours.get(a), colours.get(b)) else {
+                    continue;
+                };
+                if let Ok(delta) = delta_e_hex(hex_a, hex_b)
+                    && delta < options.min_delta_e
+                {
+                    issues.push(issue(
+                        "similar-ansi",
+                        Severity::Warning,
+                        &[a, b],
+                        delta,
+                        format!("{} and {} are hard to tell apart (ΔE {:.1}, below {:.1})", a, b, delta, options.min_delta_e),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn check_colour_blind_safety(colours: &ColourMap, options: &LintOptions, issues: &mut Vec<LintIssue>) {
+    if options.min_cvd_delta_e <= 0.0 {
+        return;
+    }
+    for group in [1..8, 9..16] {
+        let keys: Vec<String> = group.map(|i| format!("color{}", i)).collect();
+        for (i, a) in keys.iter().enumerate() {
+            for b in &keys[i + 1..] {
+                let (Some(hex_a), Some(hex_b)) = (colours.get(a), colours.get(b)) else {
+                    continue;
+                };
+                // Pairs that are already too close are reported by `similar-ansi`.
+                if delta_e_hex(hex_a, hex_b).is_ok_and(|delta| delta < options.min_delta_e) {
+                    continue;
+                }
+                let mut affected = Vec::new();
+                let mut min_delta = f64::INFINITY;
+                for cvd in CvdType::ALL {
+                    let (Ok(sim_a), Ok(sim_b)) = (simulate_hex(hex_a, cvd), simulate_hex(hex_b, cvd)) else {
+                        continue;
+                    };
+                    if let Ok(delta) = delta_e_hex(&sim_a, &sim_b)
+                        && delta < options.min_cvd_delta_e
+                    {
+                        affected.push(cvd.name());
+                        min_delta = min_delta.min(delta);
+                    }
+                }
+                if !affected.is_empty() {
+                    issues.push(issue(
+                        "colour-blind-unsafe",
+                        Severity::Warning,
+                        &[a, b],
+                        min_delta,
+                        format!("{} and {} look alike with {} (ΔE down to {:.1}, below {:.1})", a, b, affected.join(", "), min_delta, options.min_cvd_delta_e),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn check_identical_to_background(colours: &ColourMap, issues: &mut Vec<LintIssue>) {
+    let Some(bg) = colours.get("background") else {
+        return;
+    };
+    for &key in COLOUR_KEYS.iter().filter(|&&k| k != "background" && k != "color0") {
+        if let Some(hex) = colours.get(key)
+            && let Ok(delta) = delta_e_hex(hex, bg)
+            && delta < IDENTICAL_DELTA_E
+        {
+            issues.push(issue(
+                "same-as-background",
+                Severity::Error,
+                &[key, "background"],
+                delta,
+                format!("{} is indistinguishable from the background (ΔE {:.2})", key, delta),
+            ));
+        }
+    }
+}
+
+fn check_bright_ordering(colours: &ColourMap, issues: &mut Vec<LintIssue>) {
+    // Light themes conventionally darken their brights, so only dark themes are checked.
+    let Some(bg) = colours.get("background").and_then(|hex| Oklab::from_hex(hex).ok()) else {
+        return;
+    };
+    if bg.l >= 0.5 {
+        return;
+    }
+    for i in 0..8 {
+        let normal = format!("color{}", i);
+        let bright = format!("color{}", i + 8);
+        let (Some(n), Some(b)) = (
+            colours.get(&normal).and_then(|hex| Oklab::from_hex(hex).ok()),
+            colours.get(&bright).and_then(|hex| Oklab::from_hex(hex).ok()),
+        ) else {
+            continue;
+        };
+        if b.l < n.l {
+            issues.push(issue(
+                "bright-darker-than-normal",
+                Severity::Warning,
+                &[&bright, &normal],
+                n.l - b.l,
+                format!("{} is darker than {} (OKLab L {:.2} < {:.2})", bright, normal, b.l, n.l),
+            ));
+        }
+    }
+}
+
+fn check_cursor_visibility(colours: &ColourMap, issues: &mut Vec<LintIssue>) {
+    let (Some(cursor), Some(bg)) = (colours.get("cursor"), colours.get("background")) else {
+        return;
+    };
+    if let Ok(ratio) = wcag_contrast_ratio(cursor, bg)
+        && ratio < MIN_CURSOR_CONTRAST
+    {
+        issues.push(issue(
+            "invisible-cursor",
+            Severity::Error,
+            &["cursor", "background"],
+            ratio,
+            format!("cursor is nearly invisible on the background ({:.2}:1, below {:.1}:1)", ratio, MIN_CURSOR_CONTRAST),
+        ));
+    }
+}
+
+/// Runs every check over `colours`.
+pub fn lint_colours(colours: &ColourMap, options: &LintOptions) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    check_fg_contrast(colours, options, &mut issues);
+    check_identical_to_background(colours, &mut issues);
+    check_cursor_visibility(colours, &mut issues);
+    check_close_ansi_colours(colours, options, &mut issues);
+    check_colour_blind_safety(colours, options, &mut issues);
+    check_bright_ordering(colours, &mut issues);
+    issues
+}
+
+/// Lints a backup, or the live kitty.conf when `backup_name` is `None`. Returns an
+/// error (and so a non-zero exit code) when any error-level issue is found, or any
+/// issue at all with `deny_warnings`.
+pub fn lint_theme(backup_name: Option<String>, options: &LintOptions, format: OutputFormat, deny_warnings: bool) -> Result<(), io::Error> {
+    let (colours, source) = match &backup_name {
+        Some(name) => (read_colours_backup(&backup_name)?.colours, format!("backup '{}'", name)),
+        None => {
+            let config_file_path = require_kitty_config_path()?;
+            (extract_current_colours(&config_file_path)?, config_file_path.display().to_string())
+        }
+    };
+
+    let issues = lint_colours(&colours, options);
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    let warnings = issues.len() - errors;
+
+    match format {
+        OutputFormat::Json => {
+            let report = LintReport { source: &source, errors, warnings, issues: &issues };
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| io::Error::other(format!("Failed to serialise lint report: {}", e)))?;
+            println!("{}", json);
+        }
+        OutputFormat::Text => {
+            println!("Linting {}:", source);
+            for issue in &issues {
+                let label = match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                println!("  {:<8} [{}] {}", label, issue.check, issue.message);
+            }
+            println!("\n{} error(s), {} warning(s).", errors, warnings);
+        }
+    }
+
+    if errors > 0 || (deny_warnings && warnings > 0) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Theme lint failed"));
+    }
+    Ok(())
+}
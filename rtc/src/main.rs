@@ -1,6 +1,41 @@
 mod util;
 mod config;
-use config::find_kitty_config_path;
+mod backup;
+use backup::{check_all_backups, read_colours_backup};
+mod colour_space;
+mod mix;
+mod diff;
+mod generate;
+mod contrast;
+mod lint;
+mod cvd;
+mod image_palette;
+mod adjust;
+mod transition;
+mod schedule;
+mod pool;
+mod colour_names;
+mod themes;
+mod import;
+mod base16;
+mod itermcolors;
+use base16::{export_base16, import_base16};
+use import::import_kitty_theme;
+use itermcolors::{export_itermcolors, import_itermcolors};
+use themes::{apply_bundled_theme, list_bundled_themes};
+use colour_names::hex_for_colour_name;
+use pool::load_colour_pool;
+use schedule::{apply_schedule, run_schedule_daemon};
+use transition::transition_between_backups;
+use adjust::{adjust_colours_in_kitty, ColourAdjustment};
+use image_palette::palette_from_image;
+use lint::{lint_theme, LintOptions};
+use contrast::{apply_contrast_requirement, ContrastFix, ContrastMetric, ContrastRequirement};
+use generate::{derive_brights, generate_seed_palette, invert_scheme, PaletteOptions, PaletteStrategy, DEFAULT_BRIGHT_AMOUNT};
+use diff::diff_themes;
+use mix::mix_backups;
+use config::require_kitty_config_path;
+use util::is_valid_hex_code;
 mod colours;
 use colours::{
     create_colours_backup,
@@ -10,26 +45,106 @@ use colours::{
     shuffle_current_colours,
     update_kitty_config_with_colours,
     parse_colour_keys_input,
+    derive_brights_in_kitty,
+    apply_or_save_colours,
+    extract_current_colours,
     ColourMap,
+    RandomColourOptions,
+    ShuffleOptions,
     COLOUR_KEYS,
 };
 mod cli;
-use cli::Args;
+use cli::{Args, Base16Command, BackupsCommand, Command, ItermCommand, ScheduleCommand, ThemesCommand};
 use clap::Parser;
+use rand::Rng;
 use std::collections::HashMap;
 use std::io;
 
 
+fn run_command(command: Command) -> Result<(), io::Error> {
+    match command {
+        Command::Backups { action: BackupsCommand::Check } => check_all_backups(),
+        Command::Import { file, name, apply } => import_kitty_theme(&file, name, apply),
+        Command::Base16 { action: Base16Command::Import { file, name, map, apply } } => import_base16(&file, name, &map, apply),
+        Command::Base16 { action: Base16Command::Export { source, output, system, map } } => {
+            export_base16(source, output.as_deref(), system, &map)
+        }
+        Command::Iterm { action: ItermCommand::Import { file, name, apply } } => import_itermcolors(&file, name, apply),
+        Command::Iterm { action: ItermCommand::Export { source, output } } => export_itermcolors(source, output.as_deref()),
+        Command::Themes { action: ThemesCommand::List } => {
+            list_bundled_themes();
+            Ok(())
+        }
+        Command::Themes { action: ThemesCommand::Apply { theme, name } } => apply_bundled_theme(&theme, name),
+        Command::Schedule { action: ScheduleCommand::Apply } => apply_schedule(&require_kitty_config_path()?),
+        Command::Schedule { action: ScheduleCommand::Daemon } => run_schedule_daemon(&require_kitty_config_path()?),
+        Command::Mix { a, b, name, from_a, from_b, ratio, key_ratio } => {
+            mix_backups(&a, &b, &name, &from_a, &from_b, ratio, &key_ratio)
+        }
+        Command::DeriveBrights { amount, exception_keys, force_keys } => {
+            derive_brights_in_kitty(&require_kitty_config_path()?, &exception_keys, &force_keys, amount)
+        }
+        Command::Seed { colour, variant, name } => {
+            let seed_hex = colour.trim().trim_start_matches('#').to_string();
+            if !is_valid_hex_code(&seed_hex) {
+                eprintln!("Error: Invalid seed colour '{}'. Must be 6 hexadecimal characters (e.g., '#3b82f6').", colour);
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid seed colour"));
+            }
+            let palette = generate_seed_palette(&seed_hex, variant)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let variant_name = format!("{:?}", variant).to_lowercase();
+
+            println!("\nScheme generated from seed #{} ({}):", seed_hex, variant_name);
+            apply_or_save_colours(&palette, name, format!("Generated from seed #{}", seed_hex), vec!["seed".to_string(), variant_name])
+        }
+        Command::Lint { name, format, min_contrast, min_delta_e, min_cvd_delta_e, deny_warnings } => {
+            lint_theme(name, &LintOptions { min_contrast, min_delta_e, min_cvd_delta_e }, format, deny_warnings)
+        }
+        Command::Adjust { lighten, darken, saturate, desaturate, hue_rotate, warm, cool, exception_keys, force_keys } => {
+            let adjustment = ColourAdjustment {
+                lightness: lighten.or(darken.map(|d| -d)).unwrap_or(0.0),
+                chroma: saturate.or(desaturate.map(|d| -d)).unwrap_or(0.0),
+                hue_rotation: hue_rotate.unwrap_or(0.0),
+                temperature: warm.or(cool.map(|c| -c)).unwrap_or(0.0),
+            };
+            adjust_colours_in_kitty(&require_kitty_config_path()?, &exception_keys, &force_keys, &adjustment)
+        }
+        Command::Invert { source, name } => {
+            let (colours, origin) = match &source {
+                Some(backup_name) => (read_colours_backup(&source)?.colours, format!("backup '{}'", backup_name)),
+                None => (extract_current_colours(&require_kitty_config_path()?)?, "current colours".to_string()),
+            };
+            let inverted = invert_scheme(&colours)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            println!("\nInverted {}:", origin);
+            apply_or_save_colours(&inverted, name, format!("Inverted from {}", origin), vec!["inverted".to_string()])
+        }
+        Command::Transition { from, to, duration, steps, method } => {
+            transition_between_backups(&require_kitty_config_path()?, &from, &to, duration, steps, method)
+        }
+        Command::FromImage { image, variant, clusters, name } => {
+            let palette = palette_from_image(&image, variant, clusters as usize)?;
+            let file_name = image.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+            println!("\nScheme extracted from {}:", image.display());
+            apply_or_save_colours(&palette, name, format!("Extracted from image {}", file_name), vec!["image".to_string()])
+        }
+        Command::Diff { a, b } => {
+            let config_file_path = if b.is_none() { Some(require_kitty_config_path()?) } else { None };
+            diff_themes(&a, b.as_deref(), config_file_path.as_ref())
+        }
+    }
+}
+
 fn main() -> Result<(), io::Error> {
     let args = Args::parse();
 
-    let config_file_path = match find_kitty_config_path() {
-        Some(path) => path,
-        None => {
-            eprintln!("Error: kitty.conf not found. Please ensure it's in ~/.config/kitty/kitty.conf or ~/.kitty.kitty.conf");
-            return Err(io::Error::new(io::ErrorKind::NotFound, "kitty.conf not found"));
-        }
-    };
+    if let Some(command) = args.command {
+        return run_command(command);
+    }
+
+    let config_file_path = require_kitty_config_path()?;
 
     let active_modes = [
         args.random_colours,
@@ -53,21 +168,102 @@ fn main() -> Result<(), io::Error> {
         return Ok(());
     }
 
-    if (has_exception_keys || has_force_keys_for_random_shuffle_or_set) && !(args.random_colours || args.shuffle || args.set_colour) {
-        eprintln!("Error: The --exception (-e) or --force (-f) flags can only be used with --random (-r), --shuffle (-s), or --set-colour (-c).");
+    if (has_exception_keys || has_force_keys_for_random_shuffle_or_set) && !(args.random_colours || args.shuffle || args.set_colour || args.load) {
+        eprintln!("Error: The --exception (-e) or --force (-f) flags can only be used with --random (-r), --shuffle (-s), --set-colour (-c), or --load (-l).");
+        return Ok(());
+    }
+
+    if args.derive_brights && !(args.random_colours || args.set_colour) {
+        eprintln!("Error: The --derive-brights flag can only be used with --random (-r) or --set-colour (-c).");
+        return Ok(());
+    }
+    let derive_brights_amount = args.derive_brights.then(|| args.bright_amount.unwrap_or(DEFAULT_BRIGHT_AMOUNT));
+
+    if args.seed.is_some() && !(args.random_colours || args.shuffle) {
+        eprintln!("Error: The --seed flag can only be used with --random (-r) or --shuffle (-s).");
+        return Ok(());
+    }
+    let seed = args.seed.unwrap_or_else(|| rand::rng().random());
+
+    if args.min_contrast.is_some() && !(args.random_colours || args.shuffle || args.set_colour) {
+        eprintln!("Error: The --min-contrast flag can only be used with --random (-r), --shuffle (-s), or --set-colour (-c).");
+        return Ok(());
+    }
+    if args.set_colour && args.contrast_fix == Some(ContrastFix::Regenerate) {
+        eprintln!("Error: --contrast-fix regenerate only works with --random (-r) or --shuffle (-s). Use 'adjust' or 'reject' with --set-colour (-c).");
+        return Ok(());
+    }
+    let contrast_metric = args.contrast_metric.unwrap_or(ContrastMetric::Wcag);
+    if let Some(min_contrast) = args.min_contrast {
+        let (min, max) = contrast_metric.valid_range();
+        if !(min..=max).contains(&min_contrast) {
+            let metric_name = match contrast_metric {
+                ContrastMetric::Wcag => "WCAG",
+                ContrastMetric::Apca => "APCA",
+            };
+            eprintln!("Error: --min-contrast {} is out of range for {}; use a value from {} to {}.", min_contrast, metric_name, min, max);
+            return Ok(());
+        }
+    }
+    let contrast = args.min_contrast.map(|min_contrast| ContrastRequirement {
+        min_contrast,
+        metric: contrast_metric,
+        fix: args.contrast_fix.unwrap_or(ContrastFix::Adjust),
+    });
+
+    if args.hex_values.is_some() && !(args.set_colour || args.random_colours) {
+        eprintln!("Error: The --hex-values (-h) flag can only be used with --set-colour (-c) or --random (-r).");
+        return Ok(());
+    }
+    let pool_inline = if args.random_colours { args.hex_values.clone() } else { None };
+    if pool_inline.is_some() && args.strategy.is_some() {
+        eprintln!("Error: A colour pool (--hex-values with -r) cannot be combined with --strategy.");
+        return Ok(());
+    }
+    let semantic_tunables = args.hue_jitter.is_some() || args.chroma.is_some() || args.lightness.is_some();
+    if semantic_tunables && args.strategy != Some(PaletteStrategy::Semantic) {
+        eprintln!("Error: --hue-jitter, --chroma and --lightness only apply to --strategy semantic.");
+        return Ok(());
+    }
+    let pool = load_colour_pool(&args.pool_backup, args.pool_file.as_deref(), &pool_inline)?;
+    if args.without_replacement && pool.is_none() {
+        eprintln!("Error: --without-replacement needs a colour pool (--pool-backup, --pool-file, or -r -h <hex_codes>).");
         return Ok(());
     }
 
     if args.random_colours {
-        apply_random_colours_to_kitty(&config_file_path, &args.exception_keys, &args.force_keys)?;
+        let defaults = PaletteOptions::default();
+        let palette_options = PaletteOptions {
+            strategy: args.strategy.unwrap_or(PaletteStrategy::Random),
+            base_hue: args.hue,
+            hue_jitter: args.hue_jitter.unwrap_or(defaults.hue_jitter),
+            chroma: args.chroma.unwrap_or(defaults.chroma),
+            lightness: args.lightness.unwrap_or(defaults.lightness),
+        };
+        let options = RandomColourOptions {
+            palette: palette_options,
+            derive_brights_amount,
+            contrast,
+            seed,
+            pool,
+            without_replacement: args.without_replacement,
+        };
+        apply_random_colours_to_kitty(&config_file_path, &args.exception_keys, &args.force_keys, &options)?;
     } else if args.backup {
-        create_colours_backup(&config_file_path, args.name)?;
+        create_colours_backup(&config_file_path, args.name, args.description, &args.tags)?;
     } else if args.load {
-        load_colours_from_backup(&config_file_path, args.name)?;
+        load_colours_from_backup(&config_file_path, args.name, &args.exception_keys, &args.force_keys)?;
     } else if args.get_colours {
-        print_current_colours_to_terminal(&config_file_path)?;
+        print_current_colours_to_terminal(&config_file_path, args.simulate)?;
     } else if args.shuffle {
-        shuffle_current_colours(&config_file_path, &args.exception_keys, &args.force_keys)?;
+        let options = ShuffleOptions {
+            seed,
+            contrast,
+            within_groups: args.within_groups,
+            keep_bg_fg_order: args.keep_bg_fg_order,
+            derangement: args.derangement,
+        };
+        shuffle_current_colours(&config_file_path, &args.exception_keys, &args.force_keys, &options)?;
     } else if args.set_colour {
         let keys_str = args.force_keys.as_ref().expect("force_keys is required by clap for --set-colour");
         let hex_values_str = args.hex_values.as_ref().expect("hex_values is required by clap for --set-colour");
@@ -88,10 +284,13 @@ fn main() -> Result<(), io::Error> {
 
         let mut colours_to_set: ColourMap = HashMap::new();
         for (i, key_alias) in keys.into_iter().enumerate() {
-            let hex_code = &hex_values[i];
+            let hex_code = match hex_for_colour_name(&hex_values[i]) {
+                Some(named_hex) => named_hex.to_string(),
+                None => hex_values[i].trim_start_matches('#').to_string(),
+            };
 
-            if hex_code.len() != 6 || !hex_code.chars().all(|c| c.is_ascii_hexdigit()) {
-                eprintln!("Error: Invalid hex code format for '{}'. Must be 6 hexadecimal characters (e.g., '123456').", hex_code);
+            if !is_valid_hex_code(&hex_code) {
+                eprintln!("Error: '{}' is neither a 6-digit hex code (e.g., '123456') nor a CSS/X11 colour name (e.g., 'navy').", hex_values[i]);
                 return Ok(());
             }
 
@@ -104,7 +303,26 @@ fn main() -> Result<(), io::Error> {
                 return Ok(());
             }
 
-            colours_to_set.insert(full_key_name, hex_code.to_string());
+            colours_to_set.insert(full_key_name, hex_code);
+        }
+
+        if let Some(amount) = derive_brights_amount {
+            let mut merged_colours = extract_current_colours(&config_file_path)?;
+            merged_colours.extend(colours_to_set.clone());
+            let set_keys: Vec<String> = colours_to_set.keys().cloned().collect();
+            for (key, hex) in derive_brights(&merged_colours, amount, &set_keys, &[]) {
+                colours_to_set.entry(key).or_insert(hex);
+            }
+        }
+
+        if let Some(requirement) = &contrast {
+            let mut merged_colours = extract_current_colours(&config_file_path)?;
+            merged_colours.extend(colours_to_set.clone());
+            let set_keys: Vec<String> = colours_to_set.keys().cloned().collect();
+            apply_contrast_requirement(&mut merged_colours, &set_keys, requirement)?;
+            for key in set_keys {
+                colours_to_set.insert(key.clone(), merged_colours[&key].clone());
+            }
         }
 
         println!("\nSetting specific colours in Kitty config:");
@@ -119,10 +337,27 @@ fn main() -> Result<(), io::Error> {
     } else {
         println!("No operation specified.");
         println!("Use `rtc -r` to generate random colours, `rtc -b` to save, or `rtc -l` to load in, `rtc -g` to print current colours, or `rtc -s` to reorder current colours.");
+        println!("Add `--strategy <name>` to `-r` for a harmonious palette (complementary, analogous, triadic, tetradic, monochrome, base-hue, semantic).");
         println!("Add `-n <name>` to specify `backup` or `load` file name for these operations.");
-        println!("Use `-e <keys>` with `-r` or `-s` to specify colours to exclude (e.g., `-e bg` or `-e fg,c0`).");
-        println!("Use `-f <keys>` with `-r` or `-s` to specify colours to *only* affect (e.g., `-f fg` or `-f bg,c7`). Conflicts with `-e`.");
-        println!("Use `-c -f <keys> -h <hex_codes>` to set specific colours (e.g., `-c -f bg,fg -h 000000,FFFFFF`).");
+        println!("Use `-e <keys>` with `-r`, `-s` or `-l` to specify colours to exclude (e.g., `-e bg`, `-e fg,c0` or `-e c0-c7`).");
+        println!("Use `-f <keys>` with `-r`, `-s` or `-l` to specify colours to *only* affect (e.g., `-f fg` or `-f bg,c7`). Conflicts with `-e`.");
+        println!("Use `-c -f <keys> -h <hex_codes>` to set specific colours (e.g., `-c -f bg,fg -h 000000,FFFFFF` or `-h navy,ivory`).");
+        println!("Add `--pool-backup <name>`, `--pool-file <path>` or `-h <hex_codes>` to `-r` to draw from an approved palette.");
+        println!("Add `--min-contrast <ratio>` to `-r`, `-s` or `-c` to keep text readable against the background.");
+        println!("Add `--derive-brights` to `-r` or `-c`, or run `rtc derive-brights`, to recompute color8-15 from color0-7.");
+        println!("Use `rtc backups check` to validate all stored backups, or `rtc mix <a> <b> -n <name>` to blend two of them.");
+        println!("Add `--within-groups`, `--keep-bg-fg-order` or `--derangement` to `-s` to keep shuffles readable.");
+        println!("Add `--simulate protanopia|deuteranopia|tritanopia` to `-g` to preview colours as seen with colour blindness.");
+        println!("Use `rtc lint [name]` to audit the live config or a backup for readability problems.");
+        println!("Use `rtc adjust --desaturate 0.2` (or --lighten, --darken, --saturate, --hue-rotate, --warm, --cool) to tweak the current colours.");
+        println!("Use `rtc invert [name]` to turn a dark scheme into a light one, or vice versa.");
+        println!("Use `rtc transition <from> <to> --duration 2s --steps 30` to fade between two backups.");
+        println!("Use `rtc import <file.conf> [-n name] [--apply]` to import a kitty-themes file as a backup.");
+        println!("Use `rtc base16 import <file.yaml>` or `rtc base16 export [name] --system base16|base24` to share base16/base24 schemes.");
+        println!("Use `rtc iterm import <file.itermcolors>` or `rtc iterm export [name] -o <file.itermcolors>` to share iTerm2 presets.");
+        println!("Use `rtc themes list` and `rtc themes apply <name>` for bundled schemes such as Nord, Dracula or Solarized.");
+        println!("Use `rtc schedule apply` (e.g., from cron) or `rtc schedule daemon` to switch backups by time of day.");
+        println!("Use `rtc from-image <path>` to extract a scheme from a PNG or JPEG wallpaper.");
     }
 
     Ok(())
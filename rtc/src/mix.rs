@@ -0,0 +1,95 @@
+use std::{collections::HashMap, io};
+use crate::backup::{ColourBackup, read_colours_backup, write_colours_backup};
+use crate::colour_space::mix_hex;
+use crate::colours::{parse_colour_keys_input, ColourMap, COLOUR_KEYS};
+
+/// Parses per-key ratio overrides such as `bg=0.2,c8-c15=0.75`.
+fn parse_key_ratios(input: &Option<String>) -> Result<HashMap<String, f64>, io::Error> {
+    let mut ratios = HashMap::new();
+    let Some(s) = input else {
+        return Ok(ratios);
+    };
+
+    for part in s.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        let Some((keys_part, ratio_part)) = part.split_once('=') else {
+            eprintln!("Error: Invalid key ratio '{}'. Expected KEY=RATIO (e.g., 'bg=0.2').", part);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid key ratio"));
+        };
+        let ratio: f64 = match ratio_part.trim().parse() {
+            Ok(r) if (0.0..=1.0).contains(&r) => r,
+            _ => {
+                eprintln!("Error: Invalid ratio '{}' for '{}'. Must be a number between 0 and 1.", ratio_part.trim(), keys_part.trim());
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid key ratio"));
+            }
+        };
+        for key in parse_colour_keys_input(&Some(keys_part.to_string())) {
+            ratios.insert(key, ratio);
+        }
+    }
+    Ok(ratios)
+}
+
+/// Builds a new theme from two backups and saves it under `output_name`.
+///
+/// Keys listed in `from_a`/`from_b` are copied verbatim from that backup; every other
+/// key is interpolated in OKLab, where `ratio` 0.0 is pure A and 1.0 is pure B.
+pub fn mix_backups(
+    backup_a: &str,
+    backup_b: &str,
+    output_name: &str,
+    from_a_input: &Option<String>,
+    from_b_input: &Option<String>,
+    ratio: f64,
+    key_ratios_input: &Option<String>,
+) -> Result<(), io::Error> {
+    let a = read_colours_backup(&Some(backup_a.to_string()))?;
+    let b = read_colours_backup(&Some(backup_b.to_string()))?;
+
+    let from_a = parse_colour_keys_input(from_a_input);
+    let from_b = parse_colour_keys_input(from_b_input);
+    if let Some(key) = from_a.iter().find(|key| from_b.contains(key)) {
+        eprintln!("Error: Colour key '{}' cannot be taken from both backups.", key);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Conflicting key selection"));
+    }
+    let key_ratios = parse_key_ratios(key_ratios_input)?;
+
+    let mut mixed: ColourMap = HashMap::new();
+    println!("\nMixing '{}' and '{}':", backup_a, backup_b);
+
+    for &key in COLOUR_KEYS.iter() {
+        let key_string = key.to_string();
+        let (hex, origin) = match (a.colours.get(key), b.colours.get(key)) {
+            (None, None) => {
+                println!("  {:<11} unset in both", key);
+                continue;
+            }
+            (Some(hex_a), None) => (hex_a.clone(), "A only".to_string()),
+            (None, Some(hex_b)) => (hex_b.clone(), "B only".to_string()),
+            (Some(hex_a), Some(hex_b)) => {
+                if from_a.contains(&key_string) {
+                    (hex_a.clone(), "A".to_string())
+                } else if from_b.contains(&key_string) {
+                    (hex_b.clone(), "B".to_string())
+                } else {
+                    let t = key_ratios.get(key).copied().unwrap_or(ratio);
+                    let hex = mix_hex(hex_a, hex_b, t)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to mix '{}': {}", key, e)))?;
+                    (hex, format!("{:.0}% A / {:.0}% B", (1.0 - t) * 100.0, t * 100.0))
+                }
+            }
+        };
+        println!("  {:<11} #{} ({})", key, hex, origin);
+        mixed.insert(key_string, hex);
+    }
+
+    let description = Some(format!("Mix of '{}' and '{}'", backup_a, backup_b));
+    let backup = ColourBackup::new(mixed, None, description, vec!["mix".to_string()]);
+    write_colours_backup(&Some(output_name.to_string()), &backup)?;
+
+    println!("Mixed theme saved as '{}'. Load it with `rtc -l -n {}`.", output_name, output_name);
+    Ok(())
+}
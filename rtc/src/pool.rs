@@ -0,0 +1,147 @@
+use std::{fs, io, path::Path};
+use crate::backup::read_colours_backup;
+use crate::colours::ColourMap;
+use crate::util::is_valid_hex_code;
+use rand::Rng;
+
+/// A set of approved colours for `-r` to draw from, each with a relative weight.
+#[derive(Debug, Clone, Default)]
+pub struct ColourPool {
+    entries: Vec<(String, f64)>,
+}
+
+impl ColourPool {
+    /// Adds `hex` to the pool, or increases its weight if it is already there.
+    fn add(&mut self, hex: &str, weight: f64) {
+        let hex = hex.to_lowercase();
+        match self.entries.iter_mut().find(|(existing, _)| *existing == hex) {
+            Some(entry) => entry.1 += weight,
+            None => self.entries.push((hex, weight)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Parses pool entries of the form `HEX` or `HEX:WEIGHT` (weight 1 if omitted),
+    /// separated by commas, whitespace or newlines. A leading `#` on the hex code is
+    /// optional. A `#` followed by whitespace begins a comment up to the end of the
+    /// line, as does one starting a line with text that is not hex digits (such as
+    /// `#brand colours`). Anything else must be a valid entry, so a mistyped code like
+    /// `#1e90f` is reported rather than skipped.
+    pub fn parse(input: &str) -> Result<ColourPool, String> {
+        let mut pool = ColourPool::default();
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim_start();
+            if let Some(rest) = line.strip_prefix('#') {
+                let first_word = rest.split(|c: char| c == ':' || c == ',' || c.is_whitespace()).next().unwrap_or("");
+                if first_word.is_empty() || !first_word.chars().all(|c| c.is_ascii_hexdigit()) {
+                    continue;
+                }
+            }
+            for token in line.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+                if token == "#" {
+                    break;
+                }
+                let (hex_part, weight_part) = match token.split_once(':') {
+                    Some((hex, weight)) => (hex, Some(weight)),
+                    None => (token, None),
+                };
+                let hex = hex_part.strip_prefix('#').unwrap_or(hex_part);
+                if !is_valid_hex_code(hex) {
+                    return Err(format!("line {}: '{}' is not a 6-digit hex code", i + 1, hex_part));
+                }
+                let weight = match weight_part {
+                    None => 1.0,
+                    Some(w) => match w.parse::<f64>() {
+                        Ok(w) if w.is_finite() && w > 0.0 => w,
+                        _ => return Err(format!("line {}: weight '{}' for {} must be a positive number", i + 1, w, hex)),
+                    },
+                };
+                pool.add(hex, weight);
+            }
+        }
+        if pool.is_empty() {
+            return Err("the pool has no colours".to_string());
+        }
+        Ok(pool)
+    }
+
+    /// Builds a pool from the colours of a backup; colours used by several keys
+    /// weigh correspondingly more.
+    pub fn from_colours(colours: &ColourMap) -> ColourPool {
+        let mut pool = ColourPool::default();
+        let mut hexes: Vec<&String> = colours.values().collect();
+        hexes.sort();
+        for hex in hexes {
+            pool.add(hex, 1.0);
+        }
+        pool
+    }
+
+    /// Fills `keys` by weighted sampling. Without replacement, each colour is used at
+    /// most once, so the pool needs at least as many colours as there are keys.
+    pub fn draw<R: Rng + ?Sized>(&self, keys: &[String], with_replacement: bool, rng: &mut R) -> Result<ColourMap, String> {
+        if !with_replacement && self.len() < keys.len() {
+            return Err(format!(
+                "the pool has {} colour(s) but {} key(s) need one each without replacement",
+                self.len(),
+                keys.len()
+            ));
+        }
+
+        let mut available = self.entries.clone();
+        let mut drawn = ColourMap::new();
+        for key in keys {
+            let total: f64 = available.iter().map(|(_, w)| w).sum();
+            let mut target = rng.random_range(0.0..total);
+            let index = available.iter()
+                .position(|(_, w)| {
+                    target -= w;
+                    target < 0.0
+                })
+                .unwrap_or(available.len() - 1);
+            let hex = if with_replacement { available[index].0.clone() } else { available.remove(index).0 };
+            drawn.insert(key.clone(), hex);
+        }
+        Ok(drawn)
+    }
+}
+
+/// Loads the pool for `-r` from whichever source was given: a backup, a file of hex
+/// codes, or an inline `-h` list. Returns `None` when no pool was requested.
+pub fn load_colour_pool(
+    backup_name: &Option<String>,
+    file_path: Option<&Path>,
+    inline: &Option<String>,
+) -> Result<Option<ColourPool>, io::Error> {
+    let invalid = |source: String, e: String| {
+        eprintln!("Error: Invalid colour pool in {}: {}", source, e);
+        io::Error::new(io::ErrorKind::InvalidData, "Invalid colour pool")
+    };
+
+    if backup_name.is_some() {
+        let backup = read_colours_backup(backup_name)?;
+        let pool = ColourPool::from_colours(&backup.colours);
+        if pool.is_empty() {
+            return Err(invalid(format!("backup '{}'", backup_name.as_deref().unwrap_or_default()), "the backup has no colours".to_string()));
+        }
+        return Ok(Some(pool));
+    }
+    if let Some(path) = file_path {
+        let content = fs::read_to_string(path).map_err(|e| {
+            eprintln!("Error: Failed to read colour pool file {}: {}", path.display(), e);
+            io::Error::new(e.kind(), format!("Failed to read colour pool file: {}", e))
+        })?;
+        return ColourPool::parse(&content).map(Some).map_err(|e| invalid(path.display().to_string(), e));
+    }
+    if let Some(list) = inline {
+        return ColourPool::parse(list).map(Some).map_err(|e| invalid("--hex-values".to_string(), e));
+    }
+    Ok(None)
+}
@@ -0,0 +1,141 @@
+use std::{fs, io, path::PathBuf, thread, time::Duration};
+use crate::backup::read_colours_backup;
+use crate::colours::{extract_current_colours, load_colours_from_backup};
+use crate::config::get_schedule_path;
+use chrono::{Local, NaiveTime};
+
+/// How often the daemon re-reads the schedule and checks the clock. Checking
+/// rather than sleeping until the next boundary keeps it correct across suspend
+/// and clock changes.
+const DAEMON_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleEntry {
+    pub start: NaiveTime,
+    pub backup_name: String,
+}
+
+/// Parses schedule lines of the form `HH:MM = backup_name`. Blank lines and lines
+/// starting with `#` are ignored. Entries are returned sorted by start time.
+pub fn parse_schedule(content: &str) -> Result<Vec<ScheduleEntry>, Vec<String>> {
+    let mut entries: Vec<ScheduleEntry> = Vec::new();
+    let mut issues = Vec::new();
+
+    for (i, raw_line) in content.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_number = i + 1;
+        let Some((time_part, name_part)) = line.split_once('=') else {
+            issues.push(format!("line {}: expected 'HH:MM = backup_name', got '{}'", line_number, line));
+            continue;
+        };
+        let Ok(start) = NaiveTime::parse_from_str(time_part.trim(), "%H:%M") else {
+            issues.push(format!("line {}: invalid time '{}' (expected HH:MM, e.g. 07:00)", line_number, time_part.trim()));
+            continue;
+        };
+        let backup_name = name_part.trim();
+        if backup_name.is_empty() {
+            issues.push(format!("line {}: missing backup name after '='", line_number));
+            continue;
+        }
+        if entries.iter().any(|e| e.start == start) {
+            issues.push(format!("line {}: time {} is listed more than once", line_number, start.format("%H:%M")));
+            continue;
+        }
+        entries.push(ScheduleEntry { start, backup_name: backup_name.to_string() });
+    }
+
+    if entries.is_empty() && issues.is_empty() {
+        issues.push("the schedule has no entries".to_string());
+    }
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    entries.sort_by_key(|e| e.start);
+    Ok(entries)
+}
+
+/// The entry in effect at `now`: the latest one starting at or before it, or the
+/// last entry of the day when `now` is before the first boundary.
+pub fn active_entry(entries: &[ScheduleEntry], now: NaiveTime) -> Option<&ScheduleEntry> {
+    entries.iter().rev().find(|e| e.start <= now).or_else(|| entries.last())
+}
+
+fn read_schedule() -> Result<Vec<ScheduleEntry>, io::Error> {
+    let path = get_schedule_path()?;
+    let content = fs::read_to_string(&path).map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            eprintln!("Error: No schedule found at {}. Create it with lines such as:", path.display());
+            eprintln!("  07:00 = day");
+            eprintln!("  19:30 = night");
+        } else {
+            eprintln!("Error: Failed to read schedule {}: {}", path.display(), e);
+        }
+        io::Error::new(e.kind(), format!("Failed to read schedule: {}", e))
+    })?;
+
+    parse_schedule(&content).map_err(|issues| {
+        eprintln!("Error: Invalid schedule {}:", path.display());
+        for issue in &issues {
+            eprintln!("  {}", issue);
+        }
+        io::Error::new(io::ErrorKind::InvalidData, "Invalid schedule")
+    })
+}
+
+/// Whether every colour stored in the backup already matches kitty.conf. Keys
+/// missing from kitty.conf are skipped, since loading cannot set them either.
+fn is_backup_active(config_file_path: &PathBuf, backup_name: &str) -> Result<bool, io::Error> {
+    let backup = read_colours_backup(&Some(backup_name.to_string()))?;
+    let current = extract_current_colours(config_file_path)?;
+    Ok(backup.colours.iter().all(|(key, hex)| current.get(key).is_none_or(|c| c.eq_ignore_ascii_case(hex))))
+}
+
+fn apply_entry(config_file_path: &PathBuf, entry: &ScheduleEntry) -> Result<(), io::Error> {
+    if is_backup_active(config_file_path, &entry.backup_name)? {
+        println!("Scheduled backup '{}' (since {}) is already active.", entry.backup_name, entry.start.format("%H:%M"));
+        return Ok(());
+    }
+    println!("Applying scheduled backup '{}' (since {}).", entry.backup_name, entry.start.format("%H:%M"));
+    load_colours_from_backup(config_file_path, Some(entry.backup_name.clone()), &None, &None)
+}
+
+/// Loads the backup scheduled for the current time once. Does nothing if it is
+/// already applied, so it is safe to run from cron every few minutes.
+pub fn apply_schedule(config_file_path: &PathBuf) -> Result<(), io::Error> {
+    let entries = read_schedule()?;
+    let now = Local::now().time();
+    match active_entry(&entries, now) {
+        Some(entry) => apply_entry(config_file_path, entry),
+        None => Ok(()),
+    }
+}
+
+/// Runs until interrupted, switching backups as schedule boundaries pass. The
+/// schedule is re-read on every check, so edits take effect without a restart.
+pub fn run_schedule_daemon(config_file_path: &PathBuf) -> Result<(), io::Error> {
+    let mut entries = read_schedule()?;
+    let mut applied: Option<ScheduleEntry> = None;
+    println!("Schedule daemon started; checking every {}s. Press Ctrl+C to stop.", DAEMON_CHECK_INTERVAL.as_secs());
+
+    loop {
+        if let Ok(updated) = read_schedule() {
+            entries = updated;
+        } else {
+            eprintln!("Warning: Keeping the previously loaded schedule.");
+        }
+
+        let now = Local::now().time();
+        if let Some(entry) = active_entry(&entries, now)
+            && applied.as_ref() != Some(entry)
+        {
+            match apply_entry(config_file_path, entry) {
+                Ok(()) => applied = Some(entry.clone()),
+                Err(e) => eprintln!("Warning: Failed to apply '{}': {}. Retrying at the next check.", entry.backup_name, e),
+            }
+        }
+        thread::sleep(DAEMON_CHECK_INTERVAL);
+    }
+}
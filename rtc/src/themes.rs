@@ -0,0 +1,156 @@
+use std::io;
+use crate::colours::{apply_or_save_colours, ColourMap, COLOUR_KEYS};
+use crate::generate::SchemeVariant;
+use crate::util::colour_swatch;
+
+/// A well-known scheme compiled into the binary. `colours` follows the order of
+/// `COLOUR_KEYS`: foreground, background, cursor, then color0 to color15.
+pub struct BundledTheme {
+    pub name: &'static str,
+    pub variant: SchemeVariant,
+    pub description: &'static str,
+    pub colours: [&'static str; 19],
+}
+
+impl BundledTheme {
+    pub fn colour_map(&self) -> ColourMap {
+        COLOUR_KEYS.iter()
+            .zip(self.colours)
+            .map(|(&key, hex)| (key.to_string(), hex.to_string()))
+            .collect()
+    }
+}
+
+pub static BUNDLED_THEMES: [BundledTheme; 10] = [
+    BundledTheme {
+        name: "catppuccin-latte",
+        variant: SchemeVariant::Light,
+        description: "Catppuccin's light flavour",
+        colours: [
+            "4c4f69", "eff1f5", "dc8a78",
+            "5c5f77", "d20f39", "40a02b", "df8e1d", "1e66f5", "ea76cb", "179299", "acb0be",
+            "6c6f85", "d20f39", "40a02b", "df8e1d", "1e66f5", "ea76cb", "179299", "bcc0cc",
+        ],
+    },
+    BundledTheme {
+        name: "catppuccin-mocha",
+        variant: SchemeVariant::Dark,
+        description: "Catppuccin's darkest flavour, soft pastels",
+        colours: [
+            "cdd6f4", "1e1e2e", "f5e0dc",
+            "45475a", "f38ba8", "a6e3a1", "f9e2af", "89b4fa", "f5c2e7", "94e2d5", "bac2de",
+            "585b70", "f38ba8", "a6e3a1", "f9e2af", "89b4fa", "f5c2e7", "94e2d5", "a6adc8",
+        ],
+    },
+    BundledTheme {
+        name: "dracula",
+        variant: SchemeVariant::Dark,
+        description: "High-contrast purple-grey scheme with vivid accents",
+        colours: [
+            "f8f8f2", "282a36", "f8f8f2",
+            "21222c", "ff5555", "50fa7b", "f1fa8c", "bd93f9", "ff79c6", "8be9fd", "f8f8f2",
+            "6272a4", "ff6e6e", "69ff94", "ffffa5", "d6acff", "ff92df", "a4ffff", "ffffff",
+        ],
+    },
+    BundledTheme {
+        name: "gruvbox-dark",
+        variant: SchemeVariant::Dark,
+        description: "Retro groove with warm, earthy colours",
+        colours: [
+            "ebdbb2", "282828", "928374",
+            "282828", "cc241d", "98971a", "d79921", "458588", "b16286", "689d6a", "a89984",
+            "928374", "fb4934", "b8bb26", "fabd2f", "83a598", "d3869b", "8ec07c", "ebdbb2",
+        ],
+    },
+    BundledTheme {
+        name: "gruvbox-light",
+        variant: SchemeVariant::Light,
+        description: "Gruvbox on its cream background",
+        colours: [
+            "3c3836", "fbf1c7", "928374",
+            "fbf1c7", "cc241d", "98971a", "d79921", "458588", "b16286", "689d6a", "7c6f64",
+            "928374", "9d0006", "79740e", "b57614", "076678", "8f3f71", "427b58", "3c3836",
+        ],
+    },
+    BundledTheme {
+        name: "nord",
+        variant: SchemeVariant::Dark,
+        description: "Arctic, north-bluish palette",
+        colours: [
+            "d8dee9", "2e3440", "d8dee9",
+            "3b4252", "bf616a", "a3be8c", "ebcb8b", "81a1c1", "b48ead", "88c0d0", "e5e9f0",
+            "4c566a", "bf616a", "a3be8c", "ebcb8b", "81a1c1", "b48ead", "8fbcbb", "eceff4",
+        ],
+    },
+    BundledTheme {
+        name: "one-dark",
+        variant: SchemeVariant::Dark,
+        description: "Atom's One Dark",
+        colours: [
+            "abb2bf", "282c34", "528bff",
+            "282c34", "e06c75", "98c379", "e5c07b", "61afef", "c678dd", "56b6c2", "abb2bf",
+            "545862", "e06c75", "98c379", "e5c07b", "61afef", "c678dd", "56b6c2", "c8ccd4",
+        ],
+    },
+    BundledTheme {
+        name: "solarized-dark",
+        variant: SchemeVariant::Dark,
+        description: "Ethan Schoonover's Solarized on base03",
+        colours: [
+            "839496", "002b36", "93a1a1",
+            "073642", "dc322f", "859900", "b58900", "268bd2", "d33682", "2aa198", "eee8d5",
+            "002b36", "cb4b16", "586e75", "657b83", "839496", "6c71c4", "93a1a1", "fdf6e3",
+        ],
+    },
+    BundledTheme {
+        name: "solarized-light",
+        variant: SchemeVariant::Light,
+        description: "Ethan Schoonover's Solarized on base3",
+        colours: [
+            "657b83", "fdf6e3", "586e75",
+            "073642", "dc322f", "859900", "b58900", "268bd2", "d33682", "2aa198", "eee8d5",
+            "002b36", "cb4b16", "586e75", "657b83", "839496", "6c71c4", "93a1a1", "fdf6e3",
+        ],
+    },
+    BundledTheme {
+        name: "tokyo-night",
+        variant: SchemeVariant::Dark,
+        description: "Tokyo Night's 'night' style, deep blue with neon accents",
+        colours: [
+            "c0caf5", "1a1b26", "c0caf5",
+            "15161e", "f7768e", "9ece6a", "e0af68", "7aa2f7", "bb9af7", "7dcfff", "a9b1d6",
+            "414868", "f7768e", "9ece6a", "e0af68", "7aa2f7", "bb9af7", "7dcfff", "c0caf5",
+        ],
+    },
+];
+
+/// Finds a bundled theme by name, ignoring case and treating `_` and spaces like `-`.
+pub fn find_bundled_theme(name: &str) -> Option<&'static BundledTheme> {
+    let normalised = name.trim().to_lowercase().replace(['_', ' '], "-");
+    BUNDLED_THEMES.iter().find(|theme| theme.name == normalised)
+}
+
+pub fn list_bundled_themes() {
+    println!("Bundled themes:");
+    for theme in BUNDLED_THEMES.iter() {
+        let swatches: String = theme.colours[3..11].iter().map(|hex| colour_swatch(hex)).collect();
+        let variant = match theme.variant {
+            SchemeVariant::Dark => "dark",
+            SchemeVariant::Light => "light",
+        };
+        println!("  {:<18} {:<5} {}  {}", theme.name, variant, swatches, theme.description);
+    }
+    println!("\nApply one with `rtc themes apply <name>`, or save it as a backup with `-n <backup_name>`.");
+}
+
+/// Applies a bundled theme to kitty.conf, or saves it as a backup when
+/// `backup_name` is given.
+pub fn apply_bundled_theme(name: &str, backup_name: Option<String>) -> Result<(), io::Error> {
+    let Some(theme) = find_bundled_theme(name) else {
+        eprintln!("Error: No bundled theme named '{}'. Run `rtc themes list` to see the available themes.", name);
+        return Err(io::Error::new(io::ErrorKind::NotFound, "Unknown bundled theme"));
+    };
+
+    println!("\nBundled theme '{}':", theme.name);
+    apply_or_save_colours(&theme.colour_map(), backup_name, theme.description.to_string(), vec!["bundled".to_string(), theme.name.to_string()])
+}
@@ -0,0 +1,106 @@
+use std::{collections::HashMap, io, path::PathBuf, process, thread, time::Duration};
+use std::io::{IsTerminal, Write};
+use crate::backup::read_colours_backup;
+use crate::colour_space::mix_hex;
+use crate::colours::{ansi_colour_index, update_kitty_config_with_colours, ColourMap, COLOUR_KEYS};
+use clap::ValueEnum;
+
+/// How intermediate palettes reach the running terminal.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionMethod {
+    /// Write OSC 4/10/11/12 escape sequences to this terminal
+    Osc,
+    /// Call `kitty @ set-colors --all` (needs `allow_remote_control` in kitty.conf)
+    Kitty,
+}
+
+/// Interpolates every key between `from` and `to` in OKLab. Keys set on only one
+/// side keep that value throughout.
+fn interpolate_palette(from: &ColourMap, to: &ColourMap, t: f64) -> Result<ColourMap, String> {
+    let mut palette: ColourMap = HashMap::new();
+    for &key in COLOUR_KEYS.iter() {
+        let hex = match (from.get(key), to.get(key)) {
+            (Some(a), Some(b)) => mix_hex(a, b, t)?,
+            (Some(hex), None) | (None, Some(hex)) => hex.clone(),
+            (None, None) => continue,
+        };
+        palette.insert(key.to_string(), hex);
+    }
+    Ok(palette)
+}
+
+fn osc_sequence(key: &str, hex: &str) -> Option<String> {
+    let code = match key {
+        "foreground" => "10".to_string(),
+        "background" => "11".to_string(),
+        "cursor" => "12".to_string(),
+        _ => format!("4;{}", ansi_colour_index(key)?),
+    };
+    Some(format!("\x1b]{};#{}\x1b\\", code, hex))
+}
+
+fn push_palette(palette: &ColourMap, method: TransitionMethod) -> Result<(), io::Error> {
+    match method {
+        TransitionMethod::Osc => {
+            let sequences: String = COLOUR_KEYS.iter()
+                .filter_map(|&key| osc_sequence(key, palette.get(key)?))
+                .collect();
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(sequences.as_bytes())?;
+            stdout.flush()
+        }
+        TransitionMethod::Kitty => {
+            let status = process::Command::new("kitty")
+                .args(["@", "set-colors", "--all"])
+                .args(COLOUR_KEYS.iter().filter_map(|&key| Some(format!("{}=#{}", key, palette.get(key)?))))
+                .status()
+                .map_err(|e| {
+                    eprintln!("Error: Failed to run `kitty @ set-colors`: {}", e);
+                    io::Error::new(e.kind(), format!("Failed to run kitty: {}", e))
+                })?;
+            if !status.success() {
+                eprintln!("Error: `kitty @ set-colors` failed ({}). Is `allow_remote_control` enabled in kitty.conf?", status);
+                return Err(io::Error::other("kitty remote control failed"));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Fades the running terminal from backup `from_name` to backup `to_name` over
+/// `duration` in `steps` steps, then writes `to_name`'s colours to kitty.conf so the
+/// change survives a restart. With the OSC method and stdout not a terminal, only
+/// the config is written.
+pub fn transition_between_backups(
+    config_file_path: &PathBuf,
+    from_name: &str,
+    to_name: &str,
+    duration: Duration,
+    steps: u32,
+    method: TransitionMethod,
+) -> Result<(), io::Error> {
+    let from = read_colours_backup(&Some(from_name.to_string()))?.colours;
+    let to = read_colours_backup(&Some(to_name.to_string()))?.colours;
+
+    if method == TransitionMethod::Osc && !io::stdout().is_terminal() {
+        // Escape sequences would only end up in the redirected output or log.
+        eprintln!("Warning: Standard output is not a terminal, so the OSC transition is skipped.");
+    } else {
+        println!("Transitioning from '{}' to '{}' over {:.1}s in {} steps...", from_name, to_name, duration.as_secs_f64(), steps);
+
+        let pause = duration / steps;
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            let palette = interpolate_palette(&from, &to, t)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to interpolate: {}", e)))?;
+            push_palette(&palette, method)?;
+            if step < steps {
+                thread::sleep(pause);
+            }
+        }
+    }
+
+    update_kitty_config_with_colours(config_file_path, &to)?;
+    println!("\nKitty colours updated in config file with '{}'.", to_name);
+    Ok(())
+}
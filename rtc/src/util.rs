@@ -1,13 +1,17 @@
 use rand::Rng;
 
-pub fn generate_random_colour_hex() -> String {
+pub fn generate_random_colour_hex<R: Rng + ?Sized>(rng: &mut R) -> String {
     let mut bytes = [0u8; 3];
-    rand::rng().fill(&mut bytes);
+    rng.fill(&mut bytes);
     bytes.iter()
         .map(|byte| format!("{:02x}", byte))
         .collect()
 }
 
+pub fn is_valid_hex_code(hex: &str) -> bool {
+    hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 pub fn hex_to_rgb(hex: &str) -> Result<(u8, u8, u8), String> {
     if hex.len() != 6 {
         return Err("Invalid hex format. Use RRGGBB.".to_string());
@@ -21,4 +25,12 @@ pub fn hex_to_rgb(hex: &str) -> Result<(u8, u8, u8), String> {
 pub fn inverted_hex_to_rgb(hex: &str) -> Result<(u8, u8, u8), String> {
     let (r, g, b) = hex_to_rgb(hex)?;
     Ok((255 - r, 255 - g, 255 - b))
-}
\ No newline at end of file
+}
+
+/// A short block of `hex` rendered with a 24-bit background escape sequence.
+pub fn colour_swatch(hex: &str) -> String {
+    match hex_to_rgb(hex) {
+        Ok((r, g, b)) => format!("\x1b[48;2;{};{};{}m    \x1b[0m", r, g, b),
+        Err(_) => "    ".to_string(),
+    }
+}